@@ -0,0 +1,59 @@
+use crate::epsilon_nfa::EpsilonNfa;
+use crate::nfa::Nfa;
+use crate::validation;
+use jsonrpc_core::Result;
+use lammes_automata_theory::Dfa;
+use serde::{Deserialize, Serialize};
+
+/// Any kind of finite automaton this server understands. Clients do not need to tag which kind they send,
+/// because every kind has a distinct set of fields: a Dfa has a single start state, an Nfa has multiple
+/// start states and an EpsilonNfa additionally has epsilon transitions.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum Automaton {
+    Dfa(Dfa),
+    EpsilonNfa(EpsilonNfa),
+    Nfa(Nfa),
+}
+
+/// The kind of automaton that an operation should return.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputForm {
+    /// The automaton as it is constructed, possibly containing epsilon transitions.
+    EpsilonNfa,
+    /// The constructed automaton after removing epsilon transitions and applying the subset construction.
+    Dfa,
+    /// Like Dfa, but additionally minimized by the lammes_automata_theory library crate.
+    MinimalDfa,
+}
+
+impl Automaton {
+    /// Converts any kind of automaton into the most general kind, which is an automaton with epsilon transitions.
+    pub fn into_epsilon_nfa(self) -> Result<EpsilonNfa> {
+        Ok(match self {
+            Automaton::Dfa(dfa) => EpsilonNfa::from(validation::validated(&dfa)?.to_nfa()),
+            Automaton::EpsilonNfa(epsilon_nfa) => {
+                validation::ensure_valid_epsilon_nfa(&epsilon_nfa)?;
+                epsilon_nfa
+            },
+            Automaton::Nfa(nfa) => {
+                validation::ensure_valid_nfa(&nfa)?;
+                EpsilonNfa::from(nfa)
+            },
+        })
+    }
+
+    /// Converts the result of a construction into the kind of automaton the client asked for.
+    pub fn from_construction(automaton: EpsilonNfa, output_form: OutputForm) -> Result<Automaton> {
+        if let OutputForm::EpsilonNfa = output_form {
+            return Ok(Automaton::EpsilonNfa(automaton));
+        }
+        let (determinized_nfa, _) = automaton.remove_epsilon().determinize()?;
+        let mut dfa = determinized_nfa.into_dfa()?;
+        if let OutputForm::MinimalDfa = output_form {
+            dfa.minimize();
+        }
+        Ok(Automaton::Dfa(dfa))
+    }
+}
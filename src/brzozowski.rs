@@ -0,0 +1,72 @@
+use crate::epsilon_nfa::EpsilonNfa;
+use crate::regular_operations;
+use jsonrpc_core::Result;
+use lammes_automata_theory::Dfa;
+use serde::{Deserialize, Serialize};
+
+/// Every automaton produced by Brzozowski's minimization algorithm, in the order of their construction.
+#[derive(Serialize, Deserialize)]
+pub struct Brzozowski {
+    pub reversed: EpsilonNfa,
+    pub reversed_determinized: Dfa,
+    pub reversed_twice: EpsilonNfa,
+    /// The final result, which is the minimal complete Dfa of the language.
+    pub minimal: Dfa,
+}
+
+/// Minimizes the automaton by reversing it, determinizing it, reversing it again and determinizing it again.
+/// This works because determinizing the reversal of an automaton in which every state is reachable yields
+/// a minimal automaton, and the subset construction only constructs reachable states.
+/// Each determinization names the new states after the sets of states they represent.
+pub fn minimize(automaton: &EpsilonNfa) -> Result<Brzozowski> {
+    let reversed = regular_operations::reverse(automaton);
+    let (reversed_determinized, _) = reversed.remove_epsilon().determinize()?;
+    let reversed_twice = regular_operations::reverse(&EpsilonNfa::from(reversed_determinized.to_nfa()));
+    let (minimal, _) = reversed_twice.remove_epsilon().determinize()?;
+    Ok(Brzozowski {
+        reversed,
+        reversed_determinized: reversed_determinized.into_dfa()?,
+        reversed_twice,
+        minimal: minimal.into_dfa()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dfa_model::DfaModel;
+    use crate::equivalence;
+    use crate::hopcroft;
+    use crate::test_helpers::{epsilon_nfa, nfa};
+    use serde_json::json;
+
+    /// Checks that the result is as small as the one of Hopcroft's algorithm and accepts the same language.
+    fn assert_minimal(automaton: &EpsilonNfa) {
+        let minimal = DfaModel::from_dfa(&minimize(automaton).unwrap().minimal).unwrap();
+        let (determinized, _) = automaton.remove_epsilon().determinize().unwrap();
+        let (hopcroft_minimal, _, _) = hopcroft::minimize(&determinized);
+        assert_eq!(minimal.states.len(), hopcroft_minimal.states.len());
+        assert!(equivalence::check_equivalence(&minimal, &hopcroft_minimal).equivalent);
+        assert!(equivalence::check_equivalence(&minimal, &determinized).equivalent);
+    }
+
+    #[test]
+    fn minimizes_an_nfa() {
+        // Accepts all words ending in ab, with a redundant copy of q1.
+        assert_minimal(&EpsilonNfa::from(nfa(json!({
+            "alphabet": ["a", "b"], "states": ["q0", "q1", "q1'", "q2"], "start_states": ["q0"],
+            "accept_states": ["q2"],
+            "transitions": {"q0": {"a": ["q0", "q1", "q1'"], "b": ["q0"]}, "q1": {"b": ["q2"]}, "q1'": {"b": ["q2"]}}
+        }))));
+    }
+
+    #[test]
+    fn minimizes_an_epsilon_nfa() {
+        // Accepts the words a^n b^m for all n and m.
+        assert_minimal(&epsilon_nfa(json!({
+            "alphabet": ["a", "b"], "states": ["q0", "q1", "q2"], "start_states": ["q0"], "accept_states": ["q2"],
+            "transitions": {"q0": {"a": ["q0"]}, "q1": {"b": ["q1"]}},
+            "epsilon_transitions": {"q0": ["q1"], "q1": ["q2"]}
+        })));
+    }
+}
@@ -0,0 +1,244 @@
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr};
+use std::str::FromStr;
+
+/// Every option can be set in the config file, as an environment variable with this prefix and as a flag.
+const ENVIRONMENT_PREFIX: &str = "AUTOMATA_SERVER_";
+
+/// The names of all options, as used in the config file. Flags use dashes instead of underscores and
+/// environment variables are written in upper case with the prefix above.
+const OPTIONS: &[&str] = &[
+    "address",
+    "port",
+    "websocket_port",
+    "tcp_port",
+    "ipc_path",
+    "threads",
+    "cors_allowed_origins",
+    "cors_allowed_headers",
+    "cors_max_age",
+];
+
+pub const USAGE: &str = "\
+Exposes the lammes_automata_theory library crate via JSON-RPC over HTTP.
+
+Options, each also settable as an environment variable like AUTOMATA_SERVER_PORT
+or as a key in the config file. Lists are comma-separated in flags and environment
+variables and arrays in the config file. Flags take precedence over environment
+variables, which take precedence over the config file.
+    --config <path>     TOML config file to read (default: none)
+    --address <ip>      Address to bind to (default: 127.0.0.1)
+    --port <port>       Port to listen on for HTTP (default: 3030)
+    --websocket-port <port>
+                        Port to listen on for WebSocket (default: 3031)
+    --tcp-port <port>   Port to listen on for newline-delimited JSON-RPC over raw TCP
+                        (default: none, no TCP server)
+    --ipc-path <path>   Unix domain socket, or named pipe on Windows, to listen on
+                        (default: none, no IPC server)
+    --threads <count>   Number of worker threads (default: 3)
+    --cors-allowed-origins <origins>
+                        Comma-separated origins that browsers may call the server
+                        from, * for any origin. An empty list rejects every
+                        cross-origin request (default: any origin)
+    --cors-allowed-headers <headers>
+                        Comma-separated request headers that browsers may send,
+                        * for any header (default: *)
+    --cors-max-age <seconds>
+                        How long browsers may cache preflight responses
+                        (default: not cached)
+    --stdio             Serve newline-delimited JSON-RPC on stdin and stdout instead
+                        of opening any port
+    --help              Print this message";
+
+/// The configuration of the server after combining all sources.
+#[derive(Debug)]
+pub struct Config {
+    /// Whether to serve on stdin and stdout instead of the network. Only settable by flag.
+    pub stdio: bool,
+    pub address: IpAddr,
+    pub port: u16,
+    pub websocket_port: u16,
+    pub tcp_port: Option<u16>,
+    pub ipc_path: Option<String>,
+    pub threads: usize,
+    /// The origins allowed to make cross-origin requests. None means that every origin is allowed, because
+    /// jsonrpc-http-server then echoes the origin of each request. An empty list rejects every cross-origin request.
+    pub cors_allowed_origins: Option<Vec<String>>,
+    /// The headers allowed in cross-origin requests. None means that any header is allowed.
+    pub cors_allowed_headers: Option<Vec<String>>,
+    pub cors_max_age: Option<u32>,
+}
+
+/// Options read from a single source. Unset options are none.
+#[derive(Deserialize, Default, Debug)]
+#[serde(deny_unknown_fields)]
+struct Settings {
+    address: Option<IpAddr>,
+    port: Option<u16>,
+    websocket_port: Option<u16>,
+    tcp_port: Option<u16>,
+    ipc_path: Option<String>,
+    threads: Option<usize>,
+    cors_allowed_origins: Option<Vec<String>>,
+    cors_allowed_headers: Option<Vec<String>>,
+    cors_max_age: Option<u32>,
+}
+
+impl Settings {
+    fn set(&mut self, option: &str, value: &str) -> Result<(), String> {
+        match option {
+            "address" => self.address = Some(parse(option, value)?),
+            "port" => self.port = Some(parse(option, value)?),
+            "websocket_port" => self.websocket_port = Some(parse(option, value)?),
+            "tcp_port" => self.tcp_port = Some(parse(option, value)?),
+            "ipc_path" => self.ipc_path = Some(value.to_string()),
+            "threads" => self.threads = Some(parse(option, value)?),
+            "cors_allowed_origins" => self.cors_allowed_origins = Some(parse_list(value)),
+            "cors_allowed_headers" => self.cors_allowed_headers = Some(parse_list(value)),
+            "cors_max_age" => self.cors_max_age = Some(parse(option, value)?),
+            _ => return Err(format!("Unknown option {}.", option)),
+        }
+        Ok(())
+    }
+
+    /// Fills every option that is not set with the one of the other settings, which have lower precedence.
+    fn or(self, other: Settings) -> Settings {
+        Settings {
+            address: self.address.or(other.address),
+            port: self.port.or(other.port),
+            websocket_port: self.websocket_port.or(other.websocket_port),
+            tcp_port: self.tcp_port.or(other.tcp_port),
+            ipc_path: self.ipc_path.or(other.ipc_path),
+            threads: self.threads.or(other.threads),
+            cors_allowed_origins: self.cors_allowed_origins.or(other.cors_allowed_origins),
+            cors_allowed_headers: self.cors_allowed_headers.or(other.cors_allowed_headers),
+            cors_max_age: self.cors_max_age.or(other.cors_max_age),
+        }
+    }
+}
+
+/// Combines the command-line arguments, the environment variables and the config file into the configuration.
+/// Returns none if the usage has been requested instead.
+pub fn load<I>(arguments: I) -> Result<Option<Config>, String> where I: IntoIterator<Item = String> {
+    let mut flags = Settings::default();
+    let mut stdio = false;
+    let mut config_path = env::var(format!("{}CONFIG", ENVIRONMENT_PREFIX)).ok();
+    let mut arguments = arguments.into_iter();
+    while let Some(argument) = arguments.next() {
+        if argument == "--help" || argument == "-h" {
+            return Ok(None);
+        }
+        if argument == "--stdio" {
+            stdio = true;
+            continue;
+        }
+        let flag = argument.strip_prefix("--").ok_or_else(|| format!("Unexpected argument {}.", argument))?;
+        // Both "--port 3030" and "--port=3030" are accepted.
+        let (option, value) = match flag.find('=') {
+            Some(index) => (flag[..index].replace('-', "_"), flag[index + 1..].to_string()),
+            None => {
+                let value = arguments.next().ok_or_else(|| format!("The flag {} needs a value.", argument))?;
+                (flag.replace('-', "_"), value)
+            }
+        };
+        if option == "config" {
+            config_path = Some(value);
+        } else {
+            flags.set(&option, &value)?;
+        }
+    }
+
+    let mut environment = Settings::default();
+    for option in OPTIONS {
+        if let Ok(value) = env::var(format!("{}{}", ENVIRONMENT_PREFIX, option.to_uppercase())) {
+            environment.set(option, &value)?;
+        }
+    }
+
+    let file = match config_path {
+        Some(config_path) => {
+            let content = fs::read_to_string(&config_path)
+                .map_err(|error| format!("Could not read the config file {}: {}", config_path, error))?;
+            toml::from_str(&content)
+                .map_err(|error| format!("Could not parse the config file {}: {}", config_path, error))?
+        },
+        None => Settings::default(),
+    };
+
+    let settings = flags.or(environment).or(file);
+    let threads = settings.threads.unwrap_or(3);
+    if threads == 0 {
+        return Err(String::from("The server needs at least one thread."));
+    }
+    Ok(Some(Config {
+        stdio,
+        address: settings.address.unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+        port: settings.port.unwrap_or(3030),
+        websocket_port: settings.websocket_port.unwrap_or(3031),
+        tcp_port: settings.tcp_port,
+        ipc_path: settings.ipc_path,
+        threads,
+        cors_allowed_origins: settings.cors_allowed_origins,
+        cors_allowed_headers: settings.cors_allowed_headers.filter(|headers| !headers.iter().any(|header| header == "*")),
+        cors_max_age: settings.cors_max_age,
+    }))
+}
+
+fn parse<T: FromStr>(option: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("The value {} is invalid for the option {}.", value, option))
+}
+
+/// Splits a comma-separated list, as lists are given in flags and environment variables.
+fn parse_list(value: &str) -> Vec<String> {
+    value.split(',').map(str::trim).filter(|entry| !entry.is_empty()).map(String::from).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load_flags(flags: &[&str]) -> Result<Option<Config>, String> {
+        load(flags.iter().map(|flag| flag.to_string()))
+    }
+
+    #[test]
+    fn flags_accept_separate_and_attached_values() {
+        let config = load_flags(&["--port", "8080", "--address=0.0.0.0", "--threads=5"]).unwrap().unwrap();
+        assert_eq!(config.port, 8080);
+        assert_eq!(config.address, IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+        assert_eq!(config.threads, 5);
+        assert_eq!(config.websocket_port, 3031);
+        assert_eq!(config.tcp_port, None);
+        assert!(!config.stdio);
+    }
+
+    #[test]
+    fn lists_are_comma_separated_and_a_star_allows_any_header() {
+        let config = load_flags(&["--cors-allowed-origins", "http://a, http://b,", "--cors-allowed-headers=*"])
+            .unwrap()
+            .unwrap();
+        assert_eq!(config.cors_allowed_origins, Some(vec![String::from("http://a"), String::from("http://b")]));
+        assert_eq!(config.cors_allowed_headers, None);
+        let config = load_flags(&["--cors-allowed-origins="]).unwrap().unwrap();
+        assert_eq!(config.cors_allowed_origins, Some(vec![]));
+    }
+
+    #[test]
+    fn invalid_arguments_are_reported() {
+        assert!(load_flags(&["--help"]).unwrap().is_none());
+        assert!(load_flags(&["--threads", "0"]).is_err());
+        assert!(load_flags(&["--port", "many"]).is_err());
+        assert!(load_flags(&["--unknown", "1"]).is_err());
+        assert!(load_flags(&["--port"]).is_err());
+        assert!(load_flags(&["port"]).is_err());
+    }
+
+    #[test]
+    fn stdio_is_a_flag_without_value() {
+        let config = load_flags(&["--stdio", "--port", "8080"]).unwrap().unwrap();
+        assert!(config.stdio);
+        assert_eq!(config.port, 8080);
+    }
+}
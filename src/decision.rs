@@ -0,0 +1,331 @@
+use crate::dfa_model::DfaModel;
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Emptiness {
+    pub empty: bool,
+    /// A shortest accepted word, only present if the language is not empty.
+    pub accepted_word: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Universality {
+    pub universal: bool,
+    /// A shortest rejected word, only present if the language is not universal.
+    pub rejected_word: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Finiteness {
+    pub finite: bool,
+    /// A cycle proving that the language is infinite, only present if it is.
+    pub pumpable_cycle: Option<PumpableCycle>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LanguageSize {
+    /// The exact number of accepted words as a decimal number, only present if the language is finite.
+    /// It is transmitted as a string because it can exceed the range of JSON numbers.
+    pub size: Option<String>,
+    /// A cycle proving that the language is infinite, only present if it is.
+    pub pumpable_cycle: Option<PumpableCycle>,
+}
+
+/// Proves that a language is infinite: The word prefix + cycle^n + suffix is accepted for every n.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PumpableCycle {
+    pub prefix: String,
+    pub cycle: String,
+    pub suffix: String,
+    /// The states visited while reading the cycle, starting and ending with the same state.
+    pub states: Vec<String>,
+}
+
+pub fn check_emptiness(dfa: &DfaModel) -> Emptiness {
+    let accepted_word = shortest_accepted_word(dfa);
+    Emptiness { empty: accepted_word.is_none(), accepted_word }
+}
+
+/// Checks whether the automaton accepts every word over its alphabet. Missing transitions lead to rejection.
+pub fn check_universality(dfa: &DfaModel) -> Universality {
+    let rejected_word = shortest_rejected_word(dfa);
+    Universality { universal: rejected_word.is_none(), rejected_word }
+}
+
+/// Returns the shortest and, among those, alphabetically smallest accepted word, if there is any.
+pub fn shortest_accepted_word(dfa: &DfaModel) -> Option<String> {
+    shortest_word_where(dfa, |state| state.is_some_and(|state| dfa.accept_states.contains(state)))
+}
+
+/// Returns the shortest and, among those, alphabetically smallest word over the alphabet that is rejected,
+/// if there is any. Words running into a missing transition are rejected.
+pub fn shortest_rejected_word(dfa: &DfaModel) -> Option<String> {
+    shortest_word_where(dfa, |state| !state.is_some_and(|state| dfa.accept_states.contains(state)))
+}
+
+/// Checks whether the automaton accepts only finitely many words. This is the case if no cycle passes through
+/// a state that is both reachable from the start state and able to reach an accepting state.
+pub fn check_finiteness(dfa: &DfaModel) -> Finiteness {
+    let pumpable_cycle = find_pumpable_cycle(dfa);
+    Finiteness { finite: pumpable_cycle.is_none(), pumpable_cycle }
+}
+
+/// Counts the accepted words. Because no useful state lies on a cycle if the language is finite, the count of
+/// a state is the number of words leading from it to an accepting state, which is the sum of the counts of its
+/// useful successors. Those are computed first, because the search finishes every state after its successors.
+pub fn count_language(dfa: &DfaModel) -> LanguageSize {
+    let useful_states = useful_states(dfa);
+    let finishing_order = match search_useful_states(dfa, &useful_states) {
+        UsefulStates::OnCycle(state) => {
+            let pumpable_cycle = pumpable_cycle_through(dfa, state, &useful_states);
+            return LanguageSize { size: None, pumpable_cycle: Some(pumpable_cycle) };
+        },
+        UsefulStates::Acyclic(finishing_order) => finishing_order,
+    };
+    let mut counts_by_state: HashMap<&String, BigUint> = HashMap::new();
+    for state in finishing_order {
+        let mut count = BigUint::from(dfa.accept_states.contains(state) as u32);
+        for &symbol in &dfa.alphabet {
+            if let Some(target_count) = dfa.target(state, symbol).and_then(|target| counts_by_state.get(target)) {
+                count += target_count;
+            }
+        }
+        counts_by_state.insert(state, count);
+    }
+    let size = counts_by_state.remove(&dfa.start_state).unwrap_or_default();
+    LanguageSize { size: Some(size.to_string()), pumpable_cycle: None }
+}
+
+/// Returns the states that are reachable from the start state and can reach an accepting state.
+/// Only these states matter for the accepted words.
+fn useful_states(dfa: &DfaModel) -> HashSet<String> {
+    dfa.reachable_states().intersection(&dfa.co_reachable_states()).cloned().collect()
+}
+
+/// Searches a cycle through a useful state. The state is the first one the depth-first search from the start
+/// state finds on a cycle, and the cycle is a shortest one through it.
+fn find_pumpable_cycle(dfa: &DfaModel) -> Option<PumpableCycle> {
+    let useful_states = useful_states(dfa);
+    match search_useful_states(dfa, &useful_states) {
+        UsefulStates::OnCycle(state) => Some(pumpable_cycle_through(dfa, state, &useful_states)),
+        UsefulStates::Acyclic(_) => None,
+    }
+}
+
+/// The result of a depth-first search over the useful states.
+enum UsefulStates<'a> {
+    /// A state lying on a cycle of useful states.
+    OnCycle(&'a String),
+    /// All useful states, each one after all of its useful successors.
+    Acyclic(Vec<&'a String>),
+}
+
+/// Searches the useful states depth-first from the start state, trying the symbols in alphabetical order.
+/// Every useful state is reachable from the start state, so the search visits all of them, unless the
+/// language is empty and there are none. The search uses an explicit stack, so long chains cannot overflow.
+fn search_useful_states<'a>(dfa: &'a DfaModel, useful_states: &HashSet<String>) -> UsefulStates<'a> {
+    if !useful_states.contains(&dfa.start_state) {
+        return UsefulStates::Acyclic(Vec::new());
+    }
+    let alphabet = dfa.sorted_alphabet();
+    // Every entry holds a state on the current path and the index of the next symbol to try from it.
+    let mut stack = vec![(&dfa.start_state, 0)];
+    let mut states_on_path = HashSet::new();
+    states_on_path.insert(&dfa.start_state);
+    let mut finished_states = HashSet::new();
+    let mut finishing_order = Vec::new();
+    while let Some(&(state, symbol_index)) = stack.last() {
+        let symbol = match alphabet.get(symbol_index) {
+            Some(&symbol) => symbol,
+            None => {
+                stack.pop();
+                states_on_path.remove(state);
+                finished_states.insert(state);
+                finishing_order.push(state);
+                continue;
+            }
+        };
+        stack.last_mut().unwrap().1 += 1;
+        if let Some(target) = dfa.target(state, symbol).filter(|target| useful_states.contains(*target)) {
+            // Leading back onto the current path closes a cycle.
+            if states_on_path.contains(target) {
+                return UsefulStates::OnCycle(target);
+            }
+            if !finished_states.contains(target) {
+                states_on_path.insert(target);
+                stack.push((target, 0));
+            }
+        }
+    }
+    UsefulStates::Acyclic(finishing_order)
+}
+
+/// Builds the pumpable cycle through the given useful state, which must lie on a cycle of useful states.
+fn pumpable_cycle_through(dfa: &DfaModel, state: &String, useful_states: &HashSet<String>) -> PumpableCycle {
+    let is_useful = |candidate: Option<&String>| candidate.is_some_and(|candidate| useful_states.contains(candidate));
+    let is_state = |candidate: Option<&String>| candidate == Some(state);
+    let prefix = if state == &dfa.start_state {
+        String::new()
+    } else {
+        shortest_word_between(dfa, &dfa.start_state, is_useful, is_state)
+            .expect("useful states are reachable from the start state")
+    };
+    let cycle = shortest_word_between(dfa, state, is_useful, is_state)
+        .expect("the state lies on a cycle of useful states");
+    let suffix = shortest_word_from(dfa, state, |state| state.is_some_and(|state| dfa.accept_states.contains(state)))
+        .expect("useful states can reach an accepting state");
+    let mut current_state = state;
+    let mut states = vec![state.clone()];
+    for symbol in cycle.chars() {
+        current_state = dfa.target(current_state, symbol).unwrap();
+        states.push(current_state.clone());
+    }
+    PumpableCycle { prefix, cycle, suffix, states }
+}
+
+/// Searches a shortest word over the alphabet that leads from the start state into a state for which the
+/// predicate holds. Among all shortest words, the alphabetically smallest one is returned.
+/// A missing transition leads to an implicit trap state, which is passed to the predicate as none.
+pub fn shortest_word_where<P>(dfa: &DfaModel, predicate: P) -> Option<String> where P: Fn(Option<&String>) -> bool {
+    shortest_word_from(dfa, &dfa.start_state, predicate)
+}
+
+fn shortest_word_from<P>(dfa: &DfaModel, origin: &str, predicate: P) -> Option<String> where P: Fn(Option<&String>) -> bool {
+    if predicate(Some(&origin.to_string())) {
+        return Some(String::new());
+    }
+    shortest_word_between(dfa, origin, |_| true, predicate)
+}
+
+/// Searches a shortest non-empty word leading from the origin into a state for which the goal holds, passing
+/// only through allowed states in between. Among all shortest words, the alphabetically smallest one is returned.
+/// Instead of storing the word leading to every discovered state, the breadth-first search remembers the state
+/// and symbol it was discovered by, so the memory stays linear in the number of states.
+/// A missing transition leads to an implicit trap state, which is passed to the predicates as none.
+fn shortest_word_between<A, G>(dfa: &DfaModel, origin: &str, allowed: A, goal: G) -> Option<String>
+    where A: Fn(Option<&String>) -> bool,
+          G: Fn(Option<&String>) -> bool {
+    let alphabet = dfa.sorted_alphabet();
+    let origin = Some(origin.to_string());
+    let mut predecessors: HashMap<Option<String>, Option<(Option<String>, char)>> = HashMap::new();
+    predecessors.insert(origin.clone(), None);
+    let mut unprocessed_states = VecDeque::new();
+    unprocessed_states.push_back(origin);
+    while let Some(state) = unprocessed_states.pop_front() {
+        for &symbol in &alphabet {
+            let target = state.as_ref().and_then(|state| dfa.target(state, symbol)).cloned();
+            if goal(target.as_ref()) {
+                let mut word = vec![symbol];
+                let mut current_state = &state;
+                while let Some((predecessor, symbol)) = &predecessors[current_state] {
+                    word.push(*symbol);
+                    current_state = predecessor;
+                }
+                return Some(word.into_iter().rev().collect());
+            }
+            if allowed(target.as_ref()) && !predecessors.contains_key(&target) {
+                predecessors.insert(target.clone(), Some((state.clone(), symbol)));
+                unprocessed_states.push_back(target);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::dfa;
+    use serde_json::json;
+
+    #[test]
+    fn empty_language_with_cycle_on_start_state_is_finite() {
+        let dfa = dfa(json!({
+            "alphabet": ["a"], "states": ["q0"], "start_state": "q0", "accept_states": [],
+            "transitions": {"q0": {"a": "q0"}}
+        }));
+        let finiteness = check_finiteness(&dfa);
+        assert!(finiteness.finite);
+        assert!(finiteness.pumpable_cycle.is_none());
+        assert_eq!(count_language(&dfa).size.as_deref(), Some("0"));
+    }
+
+    #[test]
+    fn pumpable_cycle_proves_an_infinite_language() {
+        // Accepts all words of the form ab^nc.
+        let dfa = dfa(json!({
+            "alphabet": ["a", "b", "c"], "states": ["q0", "q1", "q2", "q3"], "start_state": "q0",
+            "accept_states": ["q2"],
+            "transitions": {"q0": {"a": "q1"}, "q1": {"b": "q3", "c": "q2"}, "q3": {"b": "q1"}}
+        }));
+        let finiteness = check_finiteness(&dfa);
+        assert!(!finiteness.finite);
+        let cycle = finiteness.pumpable_cycle.unwrap();
+        assert!(!cycle.cycle.is_empty());
+        assert_eq!(cycle.states.first(), cycle.states.last());
+        for n in 0..3 {
+            let word = format!("{}{}{}", cycle.prefix, cycle.cycle.repeat(n), cycle.suffix);
+            assert!(dfa.run(&word).0, "{}", word);
+        }
+        let size = count_language(&dfa);
+        assert_eq!(size.size, None);
+        assert!(size.pumpable_cycle.is_some());
+    }
+
+    #[test]
+    fn counts_a_finite_language() {
+        // Accepts a, b, aa, ab, ba and bb, with a useless cycle on the trap state.
+        let dfa = dfa(json!({
+            "alphabet": ["a", "b"], "states": ["q0", "q1", "q2", "trap"], "start_state": "q0",
+            "accept_states": ["q1", "q2"],
+            "transitions": {
+                "q0": {"a": "q1", "b": "q1"}, "q1": {"a": "q2", "b": "q2"},
+                "q2": {"a": "trap", "b": "trap"}, "trap": {"a": "trap", "b": "trap"}
+            }
+        }));
+        assert!(check_finiteness(&dfa).finite);
+        assert_eq!(count_language(&dfa).size.as_deref(), Some("6"));
+    }
+
+    #[test]
+    fn long_chains_do_not_overflow_the_stack() {
+        let length = 50_000;
+        let states: Vec<String> = (0..=length).map(|index| format!("q{}", index)).collect();
+        let transitions: serde_json::Map<String, serde_json::Value> = (0..length)
+            .map(|index| (states[index].clone(), json!({"a": states[index + 1]})))
+            .collect();
+        let dfa = dfa(json!({
+            "alphabet": ["a"], "states": states, "start_state": "q0", "accept_states": [states[length]],
+            "transitions": transitions
+        }));
+        assert!(check_finiteness(&dfa).finite);
+        assert_eq!(count_language(&dfa).size.as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn finds_shortest_accepted_and_rejected_words() {
+        // Accepts all words containing b, without a transition for a after the first b.
+        let dfa = dfa(json!({
+            "alphabet": ["a", "b"], "states": ["q0", "q1"], "start_state": "q0", "accept_states": ["q1"],
+            "transitions": {"q0": {"a": "q0", "b": "q1"}, "q1": {"b": "q1"}}
+        }));
+        let emptiness = check_emptiness(&dfa);
+        assert!(!emptiness.empty);
+        assert_eq!(emptiness.accepted_word.as_deref(), Some("b"));
+        let universality = check_universality(&dfa);
+        assert!(!universality.universal);
+        assert_eq!(universality.rejected_word.as_deref(), Some(""));
+    }
+
+    #[test]
+    fn missing_transitions_break_universality() {
+        let dfa = dfa(json!({
+            "alphabet": ["a", "b"], "states": ["q0"], "start_state": "q0", "accept_states": ["q0"],
+            "transitions": {"q0": {"a": "q0"}}
+        }));
+        assert_eq!(check_universality(&dfa).rejected_word.as_deref(), Some("b"));
+        assert!(check_emptiness(&dfa).accepted_word.is_some());
+    }
+}
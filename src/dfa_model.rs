@@ -0,0 +1,290 @@
+use crate::nfa::Nfa;
+use jsonrpc_core::{Error, ErrorCode, Result};
+use lammes_automata_theory::Dfa;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// Mirrors the serialized form of the Dfa of the lammes_automata_theory library crate.
+/// The library crate does not give us access to the internals of a Dfa, so every procedure that needs to
+/// inspect or build a Dfa works on this model instead. Both are converted into each other via serde.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DfaModel {
+    pub alphabet: HashSet<char>,
+    pub states: HashSet<String>,
+    pub start_state: String,
+    pub accept_states: HashSet<String>,
+    /// Maps every state to its target for each symbol.
+    /// A missing entry means that the state has no transition for that symbol.
+    #[serde(default)]
+    pub transitions: HashMap<String, HashMap<char, String>>,
+}
+
+impl DfaModel {
+    pub fn from_dfa(dfa: &Dfa) -> Result<DfaModel> {
+        serde_json::to_value(dfa)
+            .and_then(serde_json::from_value)
+            .map_err(|error| conversion_error(error.to_string()))
+    }
+
+    pub fn into_dfa(self) -> Result<Dfa> {
+        serde_json::to_value(self)
+            .and_then(serde_json::from_value)
+            .map_err(|error| conversion_error(error.to_string()))
+    }
+
+    /// Converts this automaton into an Nfa with the same states and transitions.
+    pub fn to_nfa(&self) -> Nfa {
+        let mut start_states = HashSet::new();
+        start_states.insert(self.start_state.clone());
+        Nfa {
+            alphabet: self.alphabet.clone(),
+            states: self.states.clone(),
+            start_states,
+            accept_states: self.accept_states.clone(),
+            transitions: self.transitions.iter()
+                .map(|(origin, targets_by_symbol)| {
+                    let targets_by_symbol = targets_by_symbol.iter()
+                        .map(|(symbol, target)| (*symbol, vec![target.clone()].into_iter().collect()))
+                        .collect();
+                    (origin.clone(), targets_by_symbol)
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the state that is reached from the given state by consuming the symbol, if there is one.
+    pub fn target(&self, state: &str, symbol: char) -> Option<&String> {
+        self.transitions.get(state).and_then(|targets_by_symbol| targets_by_symbol.get(&symbol))
+    }
+
+    /// Returns all states that can be reached from the start state, including the start state itself.
+    pub fn reachable_states(&self) -> HashSet<String> {
+        let mut reachable_states = HashSet::new();
+        reachable_states.insert(self.start_state.clone());
+        let mut unprocessed_states = vec![&self.start_state];
+        while let Some(state) = unprocessed_states.pop() {
+            for &symbol in &self.alphabet {
+                if let Some(target) = self.target(state, symbol) {
+                    if reachable_states.insert(target.clone()) {
+                        unprocessed_states.push(target);
+                    }
+                }
+            }
+        }
+        reachable_states
+    }
+
+    /// Returns all states from which an accepting state can be reached, including the accepting states themselves.
+    /// They are found by searching backwards from the accepting states along the reversed transitions.
+    pub fn co_reachable_states(&self) -> HashSet<String> {
+        let mut origins_by_target: HashMap<&String, Vec<&String>> = HashMap::new();
+        for origin in &self.states {
+            for &symbol in &self.alphabet {
+                if let Some(target) = self.target(origin, symbol) {
+                    origins_by_target.entry(target).or_default().push(origin);
+                }
+            }
+        }
+        let mut co_reachable_states = self.accept_states.clone();
+        let mut unprocessed_states: Vec<&String> = self.accept_states.iter().collect();
+        while let Some(state) = unprocessed_states.pop() {
+            for &origin in origins_by_target.get(state).into_iter().flatten() {
+                if co_reachable_states.insert(origin.clone()) {
+                    unprocessed_states.push(origin);
+                }
+            }
+        }
+        co_reachable_states
+    }
+
+    /// Runs the automaton on the input and returns whether it is accepted and the visited states,
+    /// in the same format as the check method of the Dfa. If a state has no transition for the next symbol,
+    /// the input is rejected and the trace ends with that state.
+    pub fn run(&self, input: &str) -> (bool, Vec<String>) {
+        let mut current_state = &self.start_state;
+        let mut trace = vec![current_state.clone()];
+        for symbol in input.chars() {
+            match self.target(current_state, symbol) {
+                Some(target) => current_state = target,
+                None => return (false, trace)
+            }
+            trace.push(current_state.clone());
+        }
+        (self.accept_states.contains(current_state), trace)
+    }
+
+    /// Removes all states that are not contained in the given set, together with their transitions
+    /// and all transitions leading to them.
+    pub fn retain_states(&mut self, states: &HashSet<String>) {
+        self.states.retain(|state| states.contains(state));
+        self.accept_states.retain(|state| states.contains(state));
+        self.transitions.retain(|origin, _| states.contains(origin));
+        for targets_by_symbol in self.transitions.values_mut() {
+            targets_by_symbol.retain(|_, target| states.contains(target));
+        }
+    }
+
+    /// Removes all unreachable states and all dead states, from which no accepting state can be reached.
+    /// The start state is always kept, even if it is dead, because every automaton needs one.
+    /// Returns the removed unreachable states and the removed dead states, each in alphabetical order.
+    pub fn trim(&mut self) -> (Vec<String>, Vec<String>) {
+        let reachable_states = self.reachable_states();
+        let co_reachable_states = self.co_reachable_states();
+        let mut unreachable_states: Vec<String> = self.states.difference(&reachable_states).cloned().collect();
+        let mut dead_states: Vec<String> = reachable_states.difference(&co_reachable_states)
+            .filter(|state| **state != self.start_state)
+            .cloned()
+            .collect();
+        unreachable_states.sort();
+        dead_states.sort();
+        let remaining_states = self.states.iter()
+            .filter(|state| !unreachable_states.contains(state) && !dead_states.contains(state))
+            .cloned()
+            .collect();
+        self.retain_states(&remaining_states);
+        (unreachable_states, dead_states)
+    }
+
+    /// Adds the missing transitions for every symbol of the given alphabet, which is added to the alphabet of
+    /// this automaton. All missing transitions lead into a fresh trap state that loops on every symbol.
+    /// Returns the name of the trap state if one had to be added.
+    pub fn complete(&mut self, alphabet: &HashSet<char>) -> Option<String> {
+        self.alphabet.extend(alphabet.iter().cloned());
+        let is_complete = self.states.iter()
+            .all(|state| self.alphabet.iter().all(|&symbol| self.target(state, symbol).is_some()));
+        if is_complete {
+            return None;
+        }
+        let trap_state = fresh_state_name("trap", &self.states);
+        self.states.insert(trap_state.clone());
+        for state in &self.states {
+            let targets_by_symbol = self.transitions.entry(state.clone()).or_default();
+            for &symbol in &self.alphabet {
+                targets_by_symbol.entry(symbol).or_insert_with(|| trap_state.clone());
+            }
+        }
+        Some(trap_state)
+    }
+
+    /// Turns this automaton into one accepting exactly the words over its alphabet that it rejected before.
+    /// The automaton is completed first, so that words running into a missing transition become accepted too.
+    /// Returns the name of the trap state if one had to be added.
+    pub fn complement(&mut self, alphabet: &HashSet<char>) -> Option<String> {
+        let trap_state = self.complete(alphabet);
+        self.accept_states = self.states.difference(&self.accept_states).cloned().collect();
+        trap_state
+    }
+
+    /// Returns the alphabet in ascending order so that procedures iterating over it produce stable results.
+    pub fn sorted_alphabet(&self) -> Vec<char> {
+        let mut alphabet: Vec<char> = self.alphabet.iter().cloned().collect();
+        alphabet.sort();
+        alphabet
+    }
+}
+
+fn conversion_error(reason: String) -> Error {
+    Error {
+        code: ErrorCode::InternalError,
+        message: format!("Could not convert between Dfa and its model: {}", reason),
+        data: None,
+    }
+}
+
+/// Returns the preferred name, extended by as many apostrophes as needed to not clash with any existing state.
+pub fn fresh_state_name(preferred_name: &str, existing_states: &HashSet<String>) -> String {
+    let mut name = preferred_name.to_string();
+    while existing_states.contains(&name) {
+        name.push('\'');
+    }
+    name
+}
+
+/// Names the states of a construction after the objects they represent, e.g. sets or pairs of states.
+/// Different objects can have the same preferred name, e.g. the pairs of "a,b" and "c" and of "a" and "b,c",
+/// so the objects named later get apostrophes appended, like fresh_state_name does.
+#[derive(Default)]
+pub struct StateNames<T> {
+    names_by_object: HashMap<T, String>,
+    names: HashSet<String>,
+}
+
+impl<T: Eq + Hash + Clone> StateNames<T> {
+    /// Returns the name of the object and whether the object is new, naming it after the preferred name if it is.
+    pub fn name(&mut self, object: &T, preferred_name: String) -> (String, bool) {
+        if let Some(name) = self.names_by_object.get(object) {
+            return (name.clone(), false);
+        }
+        let name = fresh_state_name(&preferred_name, &self.names);
+        self.names.insert(name.clone());
+        self.names_by_object.insert(object.clone(), name.clone());
+        (name, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{dfa, set};
+    use serde_json::json;
+
+    #[test]
+    fn run_rejects_at_a_missing_transition() {
+        let dfa = dfa(json!({
+            "alphabet": ["a", "b"], "states": ["q0", "q1"], "start_state": "q0", "accept_states": ["q1"],
+            "transitions": {"q0": {"a": "q1"}}
+        }));
+        assert_eq!(dfa.run("a"), (true, vec![String::from("q0"), String::from("q1")]));
+        assert_eq!(dfa.run("ab"), (false, vec![String::from("q0"), String::from("q1")]));
+        assert_eq!(dfa.run("b"), (false, vec![String::from("q0")]));
+    }
+
+    #[test]
+    fn trim_removes_unreachable_and_dead_states() {
+        let mut dfa = dfa(json!({
+            "alphabet": ["a", "b"], "states": ["q0", "q1", "dead", "unreachable"], "start_state": "q0",
+            "accept_states": ["q1"],
+            "transitions": {
+                "q0": {"a": "q1", "b": "dead"}, "dead": {"a": "dead"}, "unreachable": {"a": "q1"}
+            }
+        }));
+        assert_eq!(dfa.co_reachable_states(), set(&["q0", "q1", "unreachable"]));
+        let (unreachable_states, dead_states) = dfa.trim();
+        assert_eq!(unreachable_states, vec!["unreachable"]);
+        assert_eq!(dead_states, vec!["dead"]);
+        assert_eq!(dfa.states, set(&["q0", "q1"]));
+        assert_eq!(dfa.target("q0", 'b'), None);
+    }
+
+    #[test]
+    fn trim_keeps_a_dead_start_state() {
+        let mut dfa = dfa(json!({
+            "alphabet": ["a"], "states": ["q0"], "start_state": "q0", "accept_states": [],
+            "transitions": {"q0": {"a": "q0"}}
+        }));
+        assert_eq!(dfa.trim(), (vec![], vec![]));
+        assert_eq!(dfa.states, set(&["q0"]));
+    }
+
+    #[test]
+    fn complete_adds_a_fresh_trap_state() {
+        let mut dfa = dfa(json!({
+            "alphabet": ["a"], "states": ["trap"], "start_state": "trap", "accept_states": ["trap"],
+            "transitions": {"trap": {"a": "trap"}}
+        }));
+        let alphabet = ['a', 'b'].iter().cloned().collect();
+        assert_eq!(dfa.complete(&alphabet).as_deref(), Some("trap'"));
+        assert_eq!(dfa.target("trap", 'b').unwrap(), "trap'");
+        assert_eq!(dfa.target("trap'", 'a').unwrap(), "trap'");
+        assert_eq!(dfa.complete(&alphabet), None);
+    }
+
+    #[test]
+    fn state_names_keep_clashing_objects_apart() {
+        let mut names = StateNames::default();
+        assert_eq!(names.name(&(1, 2), String::from("n")), (String::from("n"), true));
+        assert_eq!(names.name(&(3, 4), String::from("n")), (String::from("n'"), true));
+        assert_eq!(names.name(&(1, 2), String::from("n")), (String::from("n"), false));
+    }
+}
@@ -0,0 +1,222 @@
+use crate::decision;
+use crate::dfa_model::DfaModel;
+use crate::error;
+use jsonrpc_core::Result;
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// The maximum number of words returned at once.
+pub const MAX_WORDS_PER_PAGE: usize = 10_000;
+/// The maximum length of the words to count, because the counts grow exponentially with the length.
+pub const MAX_COUNTED_WORD_LENGTH: usize = 10_000;
+/// The maximum length of the words to list, because every page can hold MAX_WORDS_PER_PAGE words of this length
+/// and the search keeps the accepting states of every length up to it.
+pub const MAX_ENUMERATED_WORD_LENGTH: usize = 1_000;
+
+/// One page of accepted words in shortlex order.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WordPage {
+    pub words: Vec<String>,
+    /// Passing this cursor to the next call continues right after the last word of this page.
+    /// It is only present if the page is full, so there might be further words.
+    pub next_cursor: Option<String>,
+}
+
+/// Lists accepted words with at most the maximum length in shortlex order, which orders words by their length
+/// first and alphabetically second. At most limit words are returned, starting right after the cursor.
+/// The words of every length are found by a depth-first search in alphabetical order, which only follows
+/// transitions into states that can still reach an accepting state with the remaining number of symbols.
+pub fn enumerate_words(dfa: &DfaModel, max_length: usize, limit: usize, cursor: Option<&str>) -> Result<WordPage> {
+    if limit > MAX_WORDS_PER_PAGE {
+        return Err(error::resource_limit_exceeded("words per page", MAX_WORDS_PER_PAGE));
+    }
+    if max_length > MAX_ENUMERATED_WORD_LENGTH {
+        return Err(error::resource_limit_exceeded("symbols of enumerated words", MAX_ENUMERATED_WORD_LENGTH));
+    }
+    let alphabet = dfa.sorted_alphabet();
+    let cursor: Option<Vec<char>> = cursor.map(|cursor| cursor.chars().collect());
+    // A finite language has no accepted word that is longer than the number of states.
+    let longest_possible_length = if decision::check_finiteness(dfa).finite {
+        max_length.min(dfa.states.len())
+    } else {
+        max_length
+    };
+    let mut exact_accepting_states = ExactAcceptingStates::new(dfa);
+    let mut words = Vec::new();
+    let first_length = cursor.as_ref().map_or(0, |cursor| cursor.len());
+    for length in first_length..=longest_possible_length {
+        if words.len() >= limit {
+            break;
+        }
+        exact_accepting_states.compute_up_to(length);
+        if !exact_accepting_states.accepts(&dfa.start_state, length) {
+            continue;
+        }
+        let cursor = cursor.as_ref().filter(|cursor| cursor.len() == length);
+        // The word leading to the current state of the search, which is shared by all entries of the stack.
+        let mut word: Vec<char> = Vec::with_capacity(length);
+        // Every entry extends the prefix of the given length of the word by the symbol, if any. It holds the state
+        // reached with the extended word and whether the extended word is a prefix of the cursor.
+        let mut stack: Vec<(usize, Option<char>, &String, bool)> = vec![(0, None, &dfa.start_state, cursor.is_some())];
+        while let Some((prefix_length, symbol, state, on_cursor_path)) = stack.pop() {
+            word.truncate(prefix_length);
+            word.extend(symbol);
+            if word.len() == length {
+                // The cursor itself has already been returned on the previous page.
+                if !on_cursor_path {
+                    words.push(word.iter().collect());
+                    if words.len() >= limit {
+                        break;
+                    }
+                }
+                continue;
+            }
+            // Push in reverse order so that the alphabetically smallest symbol is processed first.
+            for &symbol in alphabet.iter().rev() {
+                let cursor_symbol = cursor.filter(|_| on_cursor_path).map(|cursor| cursor[word.len()]);
+                if cursor_symbol.is_some_and(|cursor_symbol| symbol < cursor_symbol) {
+                    continue;
+                }
+                if let Some(target) = dfa.target(state, symbol) {
+                    if exact_accepting_states.accepts(target, length - word.len() - 1) {
+                        stack.push((word.len(), Some(symbol), target, cursor_symbol == Some(symbol)));
+                    }
+                }
+            }
+        }
+    }
+    let next_cursor = if words.len() >= limit { words.last().cloned() } else { None };
+    Ok(WordPage { words, next_cursor })
+}
+
+/// Counts the accepted words of exactly the given length. The count of a state for a length is the number of
+/// words of that length leading from the state to an accepting state, which is computed for growing lengths.
+pub fn count_words(dfa: &DfaModel, length: usize) -> Result<BigUint> {
+    if length > MAX_COUNTED_WORD_LENGTH {
+        return Err(error::resource_limit_exceeded("symbols of counted words", MAX_COUNTED_WORD_LENGTH));
+    }
+    let mut counts_by_state: HashMap<&String, BigUint> = dfa.states.iter()
+        .map(|state| (state, BigUint::from(dfa.accept_states.contains(state) as u32)))
+        .collect();
+    for _ in 0..length {
+        counts_by_state = dfa.states.iter()
+            .map(|state| {
+                let count = dfa.alphabet.iter()
+                    .filter_map(|&symbol| dfa.target(state, symbol))
+                    .filter_map(|target| counts_by_state.get(target))
+                    .sum();
+                (state, count)
+            })
+            .collect();
+    }
+    Ok(counts_by_state.remove(&dfa.start_state).unwrap_or_default())
+}
+
+/// Holds, for every length computed so far, the states from which an accepting state can be reached by
+/// reading exactly that many symbols.
+struct ExactAcceptingStates<'a> {
+    dfa: &'a DfaModel,
+    states_by_length: Vec<HashSet<&'a String>>,
+}
+
+impl<'a> ExactAcceptingStates<'a> {
+    fn new(dfa: &'a DfaModel) -> ExactAcceptingStates<'a> {
+        ExactAcceptingStates { dfa, states_by_length: vec![dfa.accept_states.iter().collect()] }
+    }
+
+    fn compute_up_to(&mut self, length: usize) {
+        while self.states_by_length.len() <= length {
+            let previous_states = self.states_by_length.last().unwrap();
+            let states = self.dfa.states.iter()
+                .filter(|state| {
+                    self.dfa.alphabet.iter()
+                        .filter_map(|&symbol| self.dfa.target(state, symbol))
+                        .any(|target| previous_states.contains(target))
+                })
+                .collect();
+            self.states_by_length.push(states);
+        }
+    }
+
+    fn accepts(&self, state: &String, length: usize) -> bool {
+        self.states_by_length[length].contains(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::dfa;
+    use jsonrpc_core::ErrorCode;
+    use serde_json::json;
+
+    fn all_words() -> DfaModel {
+        dfa(json!({
+            "alphabet": ["a", "b"], "states": ["q0"], "start_state": "q0", "accept_states": ["q0"],
+            "transitions": {"q0": {"a": "q0", "b": "q0"}}
+        }))
+    }
+
+    #[test]
+    fn lists_words_in_shortlex_order() {
+        let page = enumerate_words(&all_words(), 2, 100, None).unwrap();
+        assert_eq!(page.words, vec!["", "a", "b", "aa", "ab", "ba", "bb"]);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test]
+    fn skips_words_that_are_not_accepted() {
+        // Accepts all words ending in b, without a transition for a in the accepting state.
+        let dfa = dfa(json!({
+            "alphabet": ["a", "b"], "states": ["q0", "q1"], "start_state": "q0", "accept_states": ["q1"],
+            "transitions": {"q0": {"a": "q0", "b": "q1"}, "q1": {"b": "q1"}}
+        }));
+        let page = enumerate_words(&dfa, 3, 100, None).unwrap();
+        assert_eq!(page.words, vec!["b", "ab", "bb", "aab", "abb", "bbb"]);
+    }
+
+    #[test]
+    fn cursor_continues_after_the_last_word_of_a_full_page() {
+        let dfa = all_words();
+        let first = enumerate_words(&dfa, 2, 3, None).unwrap();
+        assert_eq!(first.words, vec!["", "a", "b"]);
+        assert_eq!(first.next_cursor.as_deref(), Some("b"));
+        let second = enumerate_words(&dfa, 2, 3, first.next_cursor.as_deref()).unwrap();
+        assert_eq!(second.words, vec!["aa", "ab", "ba"]);
+        assert_eq!(second.next_cursor.as_deref(), Some("ba"));
+        let third = enumerate_words(&dfa, 2, 3, second.next_cursor.as_deref()).unwrap();
+        assert_eq!(third.words, vec!["bb"]);
+        assert_eq!(third.next_cursor, None);
+    }
+
+    #[test]
+    fn counts_words_of_exactly_the_given_length() {
+        assert_eq!(count_words(&all_words(), 0).unwrap(), BigUint::from(1u32));
+        assert_eq!(count_words(&all_words(), 3).unwrap(), BigUint::from(8u32));
+        assert_eq!(count_words(&all_words(), 100).unwrap(), BigUint::from(1u32) << 100);
+    }
+
+    #[test]
+    fn rejects_requests_exceeding_the_limits() {
+        let error = enumerate_words(&all_words(), 2, MAX_WORDS_PER_PAGE + 1, None).unwrap_err();
+        assert_eq!(error.code, ErrorCode::ServerError(error::RESOURCE_LIMIT_EXCEEDED));
+        let error = enumerate_words(&all_words(), MAX_ENUMERATED_WORD_LENGTH + 1, 1, None).unwrap_err();
+        assert_eq!(error.code, ErrorCode::ServerError(error::RESOURCE_LIMIT_EXCEEDED));
+        let error = count_words(&all_words(), MAX_COUNTED_WORD_LENGTH + 1).unwrap_err();
+        assert_eq!(error.code, ErrorCode::ServerError(error::RESOURCE_LIMIT_EXCEEDED));
+    }
+
+    #[test]
+    fn lists_long_words_of_a_sparse_language() {
+        // Accepts the words a^n for every multiple n of 3.
+        let dfa = dfa(json!({
+            "alphabet": ["a"], "states": ["q0", "q1", "q2"], "start_state": "q0", "accept_states": ["q0"],
+            "transitions": {"q0": {"a": "q1"}, "q1": {"a": "q2"}, "q2": {"a": "q0"}}
+        }));
+        let page = enumerate_words(&dfa, MAX_ENUMERATED_WORD_LENGTH, MAX_WORDS_PER_PAGE, None).unwrap();
+        assert_eq!(page.words.len(), MAX_ENUMERATED_WORD_LENGTH / 3 + 1);
+        assert!(page.words.iter().enumerate().all(|(index, word)| *word == "a".repeat(3 * index)));
+        assert_eq!(page.next_cursor, None);
+    }
+}
@@ -0,0 +1,128 @@
+use crate::nfa::Nfa;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// A nondeterministic finite automaton that can additionally change its state without consuming a symbol.
+/// Apart from these epsilon transitions, it looks exactly like an Nfa.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EpsilonNfa {
+    #[serde(flatten)]
+    pub nfa: Nfa,
+    /// Maps every state to all the states it can move to without consuming a symbol.
+    pub epsilon_transitions: HashMap<String, HashSet<String>>,
+}
+
+impl From<Nfa> for EpsilonNfa {
+    fn from(nfa: Nfa) -> EpsilonNfa {
+        EpsilonNfa { nfa, epsilon_transitions: HashMap::new() }
+    }
+}
+
+impl EpsilonNfa {
+    /// Returns all states that are reachable from the given states by following only epsilon transitions.
+    /// The given states are always part of their closure.
+    pub fn epsilon_closure(&self, states: &HashSet<String>) -> HashSet<String> {
+        let mut closure = states.clone();
+        let mut unprocessed_states: Vec<&String> = states.iter().collect();
+        while let Some(state) = unprocessed_states.pop() {
+            for target in self.epsilon_transitions.get(state).into_iter().flatten() {
+                if closure.insert(target.clone()) {
+                    unprocessed_states.push(target);
+                }
+            }
+        }
+        closure
+    }
+
+    /// Converts this automaton into an equivalent Nfa without epsilon transitions.
+    /// The states stay the same. The start states are extended by their epsilon closure and every symbol
+    /// transition leads to the epsilon closure of its original targets. Because every set of reachable
+    /// states is thereby closed under epsilon transitions, the accepting states do not need to change.
+    pub fn remove_epsilon(&self) -> Nfa {
+        let mut transitions: HashMap<String, HashMap<char, HashSet<String>>> = HashMap::new();
+        for state in &self.nfa.states {
+            let mut state_as_set = HashSet::new();
+            state_as_set.insert(state.clone());
+            let closure = self.epsilon_closure(&state_as_set);
+            let mut targets_by_symbol = HashMap::new();
+            for &symbol in &self.nfa.alphabet {
+                let targets = self.epsilon_closure(&self.nfa.step(&closure, symbol));
+                if !targets.is_empty() {
+                    targets_by_symbol.insert(symbol, targets);
+                }
+            }
+            if !targets_by_symbol.is_empty() {
+                transitions.insert(state.clone(), targets_by_symbol);
+            }
+        }
+        Nfa {
+            alphabet: self.nfa.alphabet.clone(),
+            states: self.nfa.states.clone(),
+            start_states: self.epsilon_closure(&self.nfa.start_states),
+            accept_states: self.nfa.accept_states.clone(),
+            transitions,
+        }
+    }
+
+    /// Returns a copy of this automaton in which every state name is prefixed with the given prefix.
+    pub fn with_prefixed_states(&self, prefix: &str) -> EpsilonNfa {
+        let prefixed = |state: &String| format!("{}{}", prefix, state);
+        let prefixed_set = |states: &HashSet<String>| states.iter().map(prefixed).collect::<HashSet<String>>();
+        EpsilonNfa {
+            nfa: Nfa {
+                alphabet: self.nfa.alphabet.clone(),
+                states: prefixed_set(&self.nfa.states),
+                start_states: prefixed_set(&self.nfa.start_states),
+                accept_states: prefixed_set(&self.nfa.accept_states),
+                transitions: self.nfa.transitions.iter()
+                    .map(|(origin, targets_by_symbol)| {
+                        let targets_by_symbol = targets_by_symbol.iter()
+                            .map(|(symbol, targets)| (*symbol, prefixed_set(targets)))
+                            .collect();
+                        (prefixed(origin), targets_by_symbol)
+                    })
+                    .collect(),
+            },
+            epsilon_transitions: self.epsilon_transitions.iter()
+                .map(|(origin, targets)| (prefixed(origin), prefixed_set(targets)))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{epsilon_nfa, set};
+    use serde_json::json;
+
+    /// Accepts the words a^n b^m for all n and m, with a chain of epsilon transitions q0 -> q1 -> q2.
+    fn a_star_b_star() -> EpsilonNfa {
+        epsilon_nfa(json!({
+            "alphabet": ["a", "b"], "states": ["q0", "q1", "q2"], "start_states": ["q0"], "accept_states": ["q2"],
+            "transitions": {"q0": {"a": ["q0"]}, "q1": {"b": ["q1"]}},
+            "epsilon_transitions": {"q0": ["q1"], "q1": ["q2"]}
+        }))
+    }
+
+    #[test]
+    fn epsilon_closure_follows_chains_of_epsilon_transitions() {
+        let automaton = a_star_b_star();
+        assert_eq!(automaton.epsilon_closure(&set(&["q0"])), set(&["q0", "q1", "q2"]));
+        assert_eq!(automaton.epsilon_closure(&set(&["q1"])), set(&["q1", "q2"]));
+        assert_eq!(automaton.epsilon_closure(&set(&["q2"])), set(&["q2"]));
+    }
+
+    #[test]
+    fn remove_epsilon_keeps_the_language() {
+        let nfa = a_star_b_star().remove_epsilon();
+        assert_eq!(nfa.start_states, set(&["q0", "q1", "q2"]));
+        assert_eq!(nfa.transitions["q0"][&'b'], set(&["q1", "q2"]));
+        for word in ["", "a", "b", "aabb", "abbb"] {
+            assert!(nfa.check(word).0, "{}", word);
+        }
+        for word in ["ba", "aba"] {
+            assert!(!nfa.check(word).0, "{}", word);
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use crate::dfa_model::DfaModel;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+
+/// The result of comparing the languages of two automata.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Equivalence {
+    pub equivalent: bool,
+    /// A shortest word that is accepted by exactly one of both automata. Only present if they are not equivalent.
+    pub counterexample: Option<Counterexample>,
+}
+
+/// The result of checking whether the language of automaton a is a subset of the language of automaton b.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Inclusion {
+    pub subset: bool,
+    /// A shortest word that is accepted by a but not by b. Only present if the language of a is no subset.
+    pub counterexample: Option<Counterexample>,
+}
+
+/// A word together with the result of checking it against two automata a and b.
+/// The traces have the same format as the trace returned by check.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Counterexample {
+    pub word: String,
+    pub accepted_by_a: bool,
+    pub trace_a: Vec<String>,
+    pub accepted_by_b: bool,
+    pub trace_b: Vec<String>,
+}
+
+impl Counterexample {
+    pub fn new(word: String, a: &DfaModel, b: &DfaModel) -> Counterexample {
+        let (accepted_by_a, trace_a) = a.run(&word);
+        let (accepted_by_b, trace_b) = b.run(&word);
+        Counterexample { word, accepted_by_a, trace_a, accepted_by_b, trace_b }
+    }
+}
+
+/// Checks whether both automata accept the same language.
+pub fn check_equivalence(a: &DfaModel, b: &DfaModel) -> Equivalence {
+    let counterexample = shortest_word_where(a, b, |accepted_by_a, accepted_by_b| accepted_by_a != accepted_by_b)
+        .map(|word| Counterexample::new(word, a, b));
+    Equivalence { equivalent: counterexample.is_none(), counterexample }
+}
+
+/// Checks whether every word accepted by a is also accepted by b.
+pub fn check_inclusion(a: &DfaModel, b: &DfaModel) -> Inclusion {
+    let counterexample = shortest_word_where(a, b, |accepted_by_a, accepted_by_b| accepted_by_a && !accepted_by_b)
+        .map(|word| Counterexample::new(word, a, b));
+    Inclusion { subset: counterexample.is_none(), counterexample }
+}
+
+/// Searches a shortest word for which the predicate holds, given whether a and b accept the word.
+/// Among all shortest words, the alphabetically smallest one is returned.
+/// Both automata are run in parallel on all words over the union of their alphabets by a breadth-first search
+/// over pairs of states. A missing transition leads to an implicit trap state, represented by none.
+pub fn shortest_word_where<P>(a: &DfaModel, b: &DfaModel, predicate: P) -> Option<String>
+    where P: Fn(bool, bool) -> bool {
+    let mut alphabet: Vec<char> = a.alphabet.union(&b.alphabet).cloned().collect();
+    alphabet.sort();
+    let start = (Some(a.start_state.clone()), Some(b.start_state.clone()));
+    let mut visited_pairs = HashSet::new();
+    visited_pairs.insert(start.clone());
+    let mut unprocessed_pairs = VecDeque::new();
+    unprocessed_pairs.push_back((start, String::new()));
+    while let Some(((state_a, state_b), word)) = unprocessed_pairs.pop_front() {
+        let accepted_by_a = state_a.as_ref().is_some_and(|state| a.accept_states.contains(state));
+        let accepted_by_b = state_b.as_ref().is_some_and(|state| b.accept_states.contains(state));
+        if predicate(accepted_by_a, accepted_by_b) {
+            return Some(word);
+        }
+        for &symbol in &alphabet {
+            let next_pair = (
+                state_a.as_ref().and_then(|state| a.target(state, symbol)).cloned(),
+                state_b.as_ref().and_then(|state| b.target(state, symbol)).cloned(),
+            );
+            if visited_pairs.insert(next_pair.clone()) {
+                let mut next_word = word.clone();
+                next_word.push(symbol);
+                unprocessed_pairs.push_back((next_pair, next_word));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::dfa;
+    use serde_json::json;
+
+    /// Accepts all words over a and b with exactly two symbols.
+    fn length_two() -> DfaModel {
+        dfa(json!({
+            "alphabet": ["a", "b"], "states": ["q0", "q1", "q2", "q3"], "start_state": "q0", "accept_states": ["q2"],
+            "transitions": {
+                "q0": {"a": "q1", "b": "q1"}, "q1": {"a": "q2", "b": "q2"},
+                "q2": {"a": "q3", "b": "q3"}, "q3": {"a": "q3", "b": "q3"}
+            }
+        }))
+    }
+
+    /// Accepts only the word bb, without transitions into a trap state.
+    fn only_bb() -> DfaModel {
+        dfa(json!({
+            "alphabet": ["a", "b"], "states": ["p0", "p1", "p2"], "start_state": "p0", "accept_states": ["p2"],
+            "transitions": {"p0": {"b": "p1"}, "p1": {"b": "p2"}}
+        }))
+    }
+
+    #[test]
+    fn equivalent_automata_have_no_counterexample() {
+        // Has a redundant trap state and misses the transitions into it.
+        let partial = dfa(json!({
+            "alphabet": ["a", "b"], "states": ["r0", "r1", "r2", "dead"], "start_state": "r0", "accept_states": ["r2"],
+            "transitions": {"r0": {"a": "r1", "b": "r1"}, "r1": {"a": "r2", "b": "r2"}, "r2": {"a": "dead"}}
+        }));
+        let equivalence = check_equivalence(&length_two(), &partial);
+        assert!(equivalence.equivalent);
+        assert!(equivalence.counterexample.is_none());
+    }
+
+    #[test]
+    fn counterexample_is_the_shortest_and_alphabetically_smallest_word() {
+        let equivalence = check_equivalence(&length_two(), &only_bb());
+        assert!(!equivalence.equivalent);
+        let counterexample = equivalence.counterexample.unwrap();
+        assert_eq!(counterexample.word, "aa");
+        assert!(counterexample.accepted_by_a);
+        assert_eq!(counterexample.trace_a, vec!["q0", "q1", "q2"]);
+        assert!(!counterexample.accepted_by_b);
+        assert_eq!(counterexample.trace_b, vec!["p0"]);
+    }
+
+    #[test]
+    fn symbols_outside_an_alphabet_lead_into_the_implicit_trap_state() {
+        let all_a = dfa(json!({
+            "alphabet": ["a"], "states": ["q0"], "start_state": "q0", "accept_states": ["q0"],
+            "transitions": {"q0": {"a": "q0"}}
+        }));
+        let all_words = dfa(json!({
+            "alphabet": ["a", "b"], "states": ["p0"], "start_state": "p0", "accept_states": ["p0"],
+            "transitions": {"p0": {"a": "p0", "b": "p0"}}
+        }));
+        let counterexample = check_equivalence(&all_a, &all_words).counterexample.unwrap();
+        assert_eq!(counterexample.word, "b");
+        assert!(!counterexample.accepted_by_a);
+        assert_eq!(counterexample.trace_a, vec!["q0"]);
+        assert!(counterexample.accepted_by_b);
+        assert_eq!(counterexample.trace_b, vec!["p0", "p0"]);
+    }
+
+    #[test]
+    fn shortest_word_where_explores_the_union_of_both_alphabets() {
+        let only_a = dfa(json!({
+            "alphabet": ["a"], "states": ["q0"], "start_state": "q0", "accept_states": [],
+            "transitions": {"q0": {"a": "q0"}}
+        }));
+        let word = shortest_word_where(&only_a, &only_bb(), |_, accepted_by_b| accepted_by_b);
+        assert_eq!(word.as_deref(), Some("bb"));
+        assert_eq!(shortest_word_where(&only_a, &only_a, |accepted_by_a, _| accepted_by_a), None);
+    }
+
+    #[test]
+    fn inclusion_ignores_words_only_accepted_by_b() {
+        let inclusion = check_inclusion(&only_bb(), &length_two());
+        assert!(inclusion.subset);
+        assert!(inclusion.counterexample.is_none());
+    }
+
+    #[test]
+    fn inclusion_counterexample_is_accepted_by_a_only() {
+        let inclusion = check_inclusion(&length_two(), &only_bb());
+        assert!(!inclusion.subset);
+        let counterexample = inclusion.counterexample.unwrap();
+        assert_eq!(counterexample.word, "aa");
+        assert!(counterexample.accepted_by_a);
+        assert!(!counterexample.accepted_by_b);
+        assert_eq!(counterexample.trace_b, vec!["p0"]);
+    }
+}
@@ -0,0 +1,97 @@
+//! Errors returned by the procedures of this server. Apart from the error codes defined by the JSON-RPC
+//! specification, the following codes are used. Every error carries machine-readable data that points at
+//! the cause, so that clients can highlight it.
+//!
+//! | Code   | Meaning                                                | Data                                      |
+//! |--------|--------------------------------------------------------|-------------------------------------------|
+//! | -32001 | The automaton is structurally invalid.                 | The error diagnostics returned by validate |
+//! | -32002 | The input contains a symbol outside the alphabet.      | `{"symbol", "position"}`                  |
+//! | -32003 | A parameter references a state the automaton lacks.    | `{"state"}`                               |
+//! | -32004 | The regular expression cannot be parsed.               | `{"position"}`                            |
+//! | -32005 | The computation would exceed a resource limit.         | `{"resource", "limit"}`                   |
+//! | -32006 | Both automata must have the same alphabet but do not.  | `{"left_only", "right_only"}`             |
+//! | -32007 | The procedure panicked, which is a bug of this server. | `{"panic"}` with the panic message     |
+//! | -32602 | The parameters are invalid in any other way.           | Depends on the parameter, if any          |
+//!
+//! Parameters that cannot be deserialized at all are reported with the code -32602 and without data,
+//! because they never reach the procedures. The validate procedure reports the problems of such automata.
+//! Positions count characters, not bytes, starting with 0.
+
+use crate::validation::Diagnostic;
+use jsonrpc_core::{Error, ErrorCode};
+use serde_json::json;
+
+pub const INVALID_AUTOMATON: i64 = -32001;
+pub const SYMBOL_NOT_IN_ALPHABET: i64 = -32002;
+pub const UNKNOWN_STATE: i64 = -32003;
+pub const INVALID_REGEX: i64 = -32004;
+pub const RESOURCE_LIMIT_EXCEEDED: i64 = -32005;
+pub const INCOMPATIBLE_ALPHABETS: i64 = -32006;
+pub const PANICKED: i64 = -32007;
+
+pub fn invalid_automaton(diagnostics: Vec<Diagnostic>) -> Error {
+    Error {
+        code: ErrorCode::ServerError(INVALID_AUTOMATON),
+        message: String::from("The automaton is invalid."),
+        data: Some(json!(diagnostics)),
+    }
+}
+
+pub fn symbol_not_in_alphabet(symbol: char, position: usize) -> Error {
+    Error {
+        code: ErrorCode::ServerError(SYMBOL_NOT_IN_ALPHABET),
+        message: format!("The symbol {} at position {} is not in the alphabet.", symbol, position),
+        data: Some(json!({ "symbol": symbol, "position": position })),
+    }
+}
+
+pub fn unknown_state(state: &str) -> Error {
+    Error {
+        code: ErrorCode::ServerError(UNKNOWN_STATE),
+        message: format!("The automaton has no state named {}.", state),
+        data: Some(json!({ "state": state })),
+    }
+}
+
+pub fn invalid_regex(position: usize, reason: &str) -> Error {
+    Error {
+        code: ErrorCode::ServerError(INVALID_REGEX),
+        message: format!("Invalid regular expression at position {}: {}", position, reason),
+        data: Some(json!({ "position": position })),
+    }
+}
+
+/// The resource names what has been limited, e.g. "words per page".
+pub fn resource_limit_exceeded(resource: &str, limit: usize) -> Error {
+    Error {
+        code: ErrorCode::ServerError(RESOURCE_LIMIT_EXCEEDED),
+        message: format!("The number of {} is limited to {}.", resource, limit),
+        data: Some(json!({ "resource": resource, "limit": limit })),
+    }
+}
+
+/// Takes the symbols that only the left and only the right automaton know.
+pub fn incompatible_alphabets(left_only: Vec<char>, right_only: Vec<char>) -> Error {
+    Error {
+        code: ErrorCode::ServerError(INCOMPATIBLE_ALPHABETS),
+        message: String::from("The alphabets of both automata differ."),
+        data: Some(json!({ "left_only": left_only, "right_only": right_only })),
+    }
+}
+
+pub fn panicked(panic_message: &str) -> Error {
+    Error {
+        code: ErrorCode::ServerError(PANICKED),
+        message: format!("The procedure panicked: {}", panic_message),
+        data: Some(json!({ "panic": panic_message })),
+    }
+}
+
+/// Like the invalid params error of the JSON-RPC specification, but with data pointing at the cause.
+pub fn invalid_params(message: String, data: serde_json::Value) -> Error {
+    Error {
+        code: ErrorCode::InvalidParams,
+        message,
+        data: Some(data),
+    }
+}
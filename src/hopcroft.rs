@@ -0,0 +1,206 @@
+use crate::dfa_model::DfaModel;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+/// The trace of Hopcroft's partition refinement algorithm.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HopcroftTrace {
+    /// The states that have been removed before refining because they are not reachable from the start state.
+    pub unreachable_states: Vec<String>,
+    /// The name of the trap state that has been added to complete the automaton before refining, if any.
+    pub trap_state: Option<String>,
+    /// The partition into accepting and rejecting states that the refinement starts with.
+    pub initial_partition: Vec<Vec<String>>,
+    /// Every step that refined the partition.
+    pub steps: Vec<RefinementStep>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RefinementStep {
+    /// The block whose predecessors have been used to split the other blocks.
+    pub splitter: Vec<String>,
+    pub symbol: char,
+    /// The partition after splitting every block that has states with and without a transition into the splitter.
+    pub partition: Vec<Vec<String>>,
+}
+
+/// Minimizes the automaton with Hopcroft's algorithm. Unreachable states are removed and missing transitions
+/// are completed with a trap state first. The partition starts with the accepting and the rejecting states.
+/// A worklist holds the splitters still to be used, starting with the smaller block of the initial partition.
+/// Each splitter and symbol split every block into the states that move into the splitter with that symbol and
+/// those that do not. If a split block is still in the worklist, both halves replace it there, otherwise only
+/// the smaller half is added. Every new state of the result is named after the alphabetically smallest state
+/// of its block. Besides the minimized automaton and the trace, a map from every new state name to the old
+/// state names of its block is returned, which does not contain the trap state.
+pub fn minimize(dfa: &DfaModel) -> (DfaModel, HashMap<String, HashSet<String>>, HopcroftTrace) {
+    let mut dfa = dfa.clone();
+    let reachable_states = dfa.reachable_states();
+    let mut unreachable_states: Vec<String> = dfa.states.difference(&reachable_states).cloned().collect();
+    unreachable_states.sort();
+    dfa.retain_states(&reachable_states);
+    let trap_state = dfa.complete(&dfa.alphabet.clone());
+    let alphabet = dfa.sorted_alphabet();
+
+    let accepting_block: BTreeSet<String> = dfa.accept_states.iter().cloned().collect();
+    let rejecting_block: BTreeSet<String> = dfa.states.difference(&dfa.accept_states).cloned().collect();
+    let mut partition: Vec<BTreeSet<String>> = vec![accepting_block.clone(), rejecting_block.clone()]
+        .into_iter()
+        .filter(|block| !block.is_empty())
+        .collect();
+    partition.sort();
+    let initial_partition = partition_as_lists(&partition);
+    let mut worklist = VecDeque::new();
+    if !accepting_block.is_empty() && !rejecting_block.is_empty() {
+        worklist.push_back(if accepting_block.len() <= rejecting_block.len() { accepting_block } else { rejecting_block });
+    }
+
+    let mut steps = Vec::new();
+    while let Some(splitter) = worklist.pop_front() {
+        for &symbol in &alphabet {
+            // The automaton is complete, so every state has a target.
+            let predecessors: HashSet<&String> = dfa.states.iter()
+                .filter(|state| splitter.contains(dfa.target(state, symbol).unwrap()))
+                .collect();
+            let mut refined_partition = Vec::new();
+            let mut refined = false;
+            for block in partition {
+                let (inside, outside): (BTreeSet<String>, BTreeSet<String>) = block.iter()
+                    .cloned()
+                    .partition(|state| predecessors.contains(state));
+                if inside.is_empty() || outside.is_empty() {
+                    refined_partition.push(block);
+                    continue;
+                }
+                refined = true;
+                if let Some(position) = worklist.iter().position(|waiting_block| *waiting_block == block) {
+                    worklist.remove(position);
+                    worklist.push_back(inside.clone());
+                    worklist.push_back(outside.clone());
+                } else if inside.len() <= outside.len() {
+                    worklist.push_back(inside.clone());
+                } else {
+                    worklist.push_back(outside.clone());
+                }
+                refined_partition.push(inside);
+                refined_partition.push(outside);
+            }
+            refined_partition.sort();
+            partition = refined_partition;
+            if refined {
+                steps.push(RefinementStep {
+                    splitter: splitter.iter().cloned().collect(),
+                    symbol,
+                    partition: partition_as_lists(&partition),
+                });
+            }
+        }
+    }
+
+    let (minimized_dfa, mut old_names_by_their_new_names) = merge_blocks(&dfa, &partition);
+    // The client does not know the trap state, which is reported in the trace instead.
+    if let Some(trap_state) = &trap_state {
+        for old_names in old_names_by_their_new_names.values_mut() {
+            old_names.remove(trap_state);
+        }
+    }
+    let trace = HopcroftTrace { unreachable_states, trap_state, initial_partition, steps };
+    (minimized_dfa, old_names_by_their_new_names, trace)
+}
+
+/// Builds the automaton whose states are the blocks of the partition, each named after its smallest state.
+pub fn merge_blocks(dfa: &DfaModel, partition: &[BTreeSet<String>]) -> (DfaModel, HashMap<String, HashSet<String>>) {
+    let mut new_names_by_their_old_names = HashMap::new();
+    let mut old_names_by_their_new_names = HashMap::new();
+    for block in partition {
+        let new_name = block.iter().next().unwrap().clone();
+        for old_name in block {
+            new_names_by_their_old_names.insert(old_name.clone(), new_name.clone());
+        }
+        old_names_by_their_new_names.insert(new_name, block.iter().cloned().collect());
+    }
+    let rename = |state: &String| new_names_by_their_old_names[state].clone();
+    let minimized_dfa = DfaModel {
+        alphabet: dfa.alphabet.clone(),
+        states: old_names_by_their_new_names.keys().cloned().collect(),
+        start_state: rename(&dfa.start_state),
+        accept_states: dfa.accept_states.iter().map(rename).collect(),
+        transitions: dfa.transitions.iter()
+            .map(|(origin, targets_by_symbol)| {
+                let targets_by_symbol = targets_by_symbol.iter()
+                    .map(|(symbol, target)| (*symbol, rename(target)))
+                    .collect();
+                (rename(origin), targets_by_symbol)
+            })
+            .collect(),
+    };
+    (minimized_dfa, old_names_by_their_new_names)
+}
+
+fn partition_as_lists(partition: &[BTreeSet<String>]) -> Vec<Vec<String>> {
+    partition.iter().map(|block| block.iter().cloned().collect()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{dfa, set};
+    use serde_json::json;
+
+    #[test]
+    fn merges_equivalent_states_without_refining() {
+        // Accepts all words ending in a, with two redundant copies of each state.
+        let (minimal, old_names_by_their_new_names, trace) = minimize(&dfa(json!({
+            "alphabet": ["a", "b"], "states": ["q0", "q1", "q2", "q3"], "start_state": "q0",
+            "accept_states": ["q1", "q3"],
+            "transitions": {
+                "q0": {"a": "q1", "b": "q2"}, "q1": {"a": "q1", "b": "q2"},
+                "q2": {"a": "q3", "b": "q2"}, "q3": {"a": "q3", "b": "q2"}
+            }
+        })));
+        assert_eq!(trace.initial_partition, vec![vec!["q0", "q2"], vec!["q1", "q3"]]);
+        assert!(trace.steps.is_empty());
+        assert_eq!(trace.trap_state, None);
+        assert_eq!(minimal.states, set(&["q0", "q1"]));
+        assert_eq!(minimal.start_state, "q0");
+        assert_eq!(minimal.accept_states, set(&["q1"]));
+        assert_eq!(minimal.target("q0", 'a').unwrap(), "q1");
+        assert_eq!(minimal.target("q1", 'b').unwrap(), "q0");
+        assert_eq!(old_names_by_their_new_names["q0"], set(&["q0", "q2"]));
+        assert_eq!(old_names_by_their_new_names["q1"], set(&["q1", "q3"]));
+    }
+
+    #[test]
+    fn records_every_refinement_step() {
+        // Accepts only the word ab and has an unreachable state u.
+        let (minimal, old_names_by_their_new_names, trace) = minimize(&dfa(json!({
+            "alphabet": ["a", "b"], "states": ["q0", "q1", "q2", "u"], "start_state": "q0", "accept_states": ["q2"],
+            "transitions": {"q0": {"a": "q1"}, "q1": {"b": "q2"}, "u": {"a": "q0"}}
+        })));
+        assert_eq!(trace.unreachable_states, vec!["u"]);
+        assert_eq!(trace.trap_state.as_deref(), Some("trap"));
+        assert_eq!(trace.initial_partition, vec![vec!["q0", "q1", "trap"], vec!["q2"]]);
+        assert_eq!(trace.steps.len(), 2);
+        assert_eq!(trace.steps[0].splitter, vec!["q2"]);
+        assert_eq!(trace.steps[0].symbol, 'b');
+        assert_eq!(trace.steps[0].partition, vec![vec!["q0", "trap"], vec!["q1"], vec!["q2"]]);
+        assert_eq!(trace.steps[1].splitter, vec!["q1"]);
+        assert_eq!(trace.steps[1].symbol, 'a');
+        assert_eq!(trace.steps[1].partition, vec![vec!["q0"], vec!["q1"], vec!["q2"], vec!["trap"]]);
+        assert_eq!(minimal.states, set(&["q0", "q1", "q2", "trap"]));
+        assert_eq!(old_names_by_their_new_names["q0"], set(&["q0"]));
+        assert_eq!(old_names_by_their_new_names["trap"], set(&[]));
+    }
+
+    #[test]
+    fn trap_state_is_not_an_old_name() {
+        // Accepts only the word a. The dead state d has no transition, so it merges with the added trap state.
+        let (minimal, old_names_by_their_new_names, trace) = minimize(&dfa(json!({
+            "alphabet": ["a"], "states": ["d", "q0", "q1"], "start_state": "q0", "accept_states": ["q1"],
+            "transitions": {"q0": {"a": "q1"}, "q1": {"a": "d"}}
+        })));
+        assert_eq!(trace.trap_state.as_deref(), Some("trap"));
+        assert_eq!(minimal.states, set(&["d", "q0", "q1"]));
+        assert_eq!(minimal.target("d", 'a').unwrap(), "d");
+        assert_eq!(old_names_by_their_new_names["d"], set(&["d"]));
+    }
+}
@@ -1,19 +1,237 @@
-use jsonrpc_core::{IoHandler, Params, Value};
-use jsonrpc_http_server::ServerBuilder;
+mod automaton;
+mod brzozowski;
+mod config;
+mod decision;
+mod dfa_model;
+mod enumeration;
+mod epsilon_nfa;
+mod equivalence;
+mod error;
+mod hopcroft;
+mod nfa;
+mod panic_guard;
+mod product;
+mod regex;
+mod regular_operations;
+mod simulation;
+mod state_elimination;
+mod stdio;
+mod table_filling;
+#[cfg(test)]
+mod test_helpers;
+mod validation;
+
+use jsonrpc_core::{MetaIoHandler, Value};
+use jsonrpc_http_server::cors::AccessControlAllowHeaders;
+use jsonrpc_http_server::{AccessControlAllowOrigin, DomainsValidation, ServerBuilder};
 use jsonrpc_core::Result;
 use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{PubSubHandler, Session};
 use lammes_automata_theory::Dfa;
 use std::collections::{HashMap, HashSet};
-use std::cmp::min;
+use std::env;
+use std::fmt;
+use std::net::SocketAddr;
+use std::process;
+use std::sync::Arc;
+use nfa::Nfa;
+use panic_guard::PanicGuard;
+use epsilon_nfa::EpsilonNfa;
+use regex::Regex;
+use state_elimination::StateElimination;
+use equivalence::{Equivalence, Inclusion};
+use automaton::{Automaton, OutputForm};
+use decision::{Emptiness, Finiteness, LanguageSize, Universality};
+use enumeration::WordPage;
+use table_filling::TableFilling;
+use hopcroft::HopcroftTrace;
+use brzozowski::Brzozowski;
+use validation::Diagnostic;
+use product::{AlphabetHandling, ProductOperation, StatePairsByName};
+use simulation::{Simulation, SimulationImpl};
 
 /// Holds all methods which are callable over this RCP server.
+/// The errors these methods return are described in the error module.
 #[rpc]
 pub trait Rpc {
-    /// Delegates to the check method in the lammes_automata_theory library crate.
-    /// The documentation can be found there.
+    /// Checks the structure of a submitted Dfa and returns every problem found, with errors first and warnings
+    /// second. Errors make the automaton unusable, e.g. transitions referencing unknown states, a missing start
+    /// state, multiple targets for the same symbol or symbols outside the alphabet. Warnings point at missing
+    /// transitions, unreachable states and dead states. The Dfa is taken as plain JSON, so that even automata
+    /// that cannot be deserialized can be validated. An empty list means that no problem has been found.
+    #[rpc(name = "validate")]
+    fn validate(&self, dfa: Value) -> Result<Vec<Diagnostic>>;
+
+    /// Checks whether the Dfa accepts the input, like the check method in the lammes_automata_theory library crate.
+    /// It returns whether the input is accepted and the visited states, starting with the start state.
+    /// Unlike the library crate, it also supports automata with missing transitions: Running into one rejects
+    /// the input and ends the trace with the state that lacks the transition.
     #[rpc(name = "check")]
     fn check(&self, dfa: Dfa, input: String) -> Result<(bool, Vec<String>)>;
 
+    /// Checks whether the nondeterministic automaton accepts the input.
+    /// Like check, it returns whether the input is accepted and a trace. But because a nondeterministic
+    /// automaton can be in multiple states at once, the trace contains the set of all reachable states
+    /// for every input position, starting with the start states. Like check, it rejects invalid automata and
+    /// input symbols outside the alphabet.
+    #[rpc(name = "nfa_check")]
+    fn nfa_check(&self, nfa: Nfa, input: String) -> Result<(bool, Vec<HashSet<String>>)>;
+
+    /// Converts the nondeterministic automaton into a Dfa using the subset construction.
+    /// Every state of the Dfa is named after the set of states it represents, e.g. "{q0,q1}". If the names of
+    /// two sets clash, which is possible if state names contain commas, apostrophes are appended to one of them.
+    /// Like minimize, this method also returns a map that maps every new state name to the set of old
+    /// state names it represents, so clients do not need to parse the state names.
+    #[rpc(name = "determinize")]
+    fn determinize(&self, nfa: Nfa) -> Result<(Dfa, HashMap<String, HashSet<String>>)>;
+
+    /// Returns all states that the automaton can reach from the given state without consuming a symbol,
+    /// including the state itself.
+    #[rpc(name = "epsilon_closure")]
+    fn epsilon_closure(&self, automaton: EpsilonNfa, state: String) -> Result<HashSet<String>>;
+
+    /// Converts the automaton into an equivalent nondeterministic automaton without epsilon transitions.
+    /// The states are kept, only the start states and the transitions change.
+    #[rpc(name = "remove_epsilon")]
+    fn remove_epsilon(&self, automaton: EpsilonNfa) -> Result<Nfa>;
+
+    /// Compiles the regular expression into an automaton with epsilon transitions using the Thompson construction.
+    /// The supported syntax is described at Regex::parse. Union is written as | or +, the Kleene star as *,
+    /// the empty word as ε and the empty language as ∅. A backslash escapes them to denote them as symbols.
+    /// The length and the nesting depth of regular expressions are limited, see regex::MAX_REGEX_LENGTH and
+    /// regex::MAX_NESTING_DEPTH.
+    #[rpc(name = "regex_to_nfa")]
+    fn regex_to_nfa(&self, regex: String) -> Result<EpsilonNfa>;
+
+    /// Compiles the regular expression into a minimal Dfa. The automaton of the Thompson construction is
+    /// freed of epsilon transitions, determinized and then minimized by the lammes_automata_theory library crate.
+    #[rpc(name = "regex_to_dfa")]
+    fn regex_to_dfa(&self, regex: String) -> Result<Dfa>;
+
+    /// Converts the Dfa into an equivalent regular expression using state elimination.
+    /// The states listed in the optional elimination order are eliminated first and in the given order,
+    /// the remaining states follow in alphabetical order. If include_steps is true, the result also contains
+    /// the generalized automaton after each elimination, so that clients can display every step.
+    /// The regular expressions can grow exponentially, so their size and depth are limited, including the
+    /// steps, see MAX_REGEX_SIZE and MAX_REGEX_DEPTH.
+    #[rpc(name = "dfa_to_regex")]
+    fn dfa_to_regex(&self, dfa: Dfa, elimination_order: Option<Vec<String>>, include_steps: Option<bool>) -> Result<StateElimination>;
+
+    /// Checks whether both Dfas accept the same language. If they do not, a shortest word that is accepted by
+    /// only one of them is returned, together with the traces of both automata on that word.
+    /// Missing transitions are treated as leading into a trap state.
+    #[rpc(name = "equivalent")]
+    fn equivalent(&self, dfa_a: Dfa, dfa_b: Dfa) -> Result<Equivalence>;
+
+    /// Checks whether every word accepted by dfa_a is also accepted by dfa_b. If not, a shortest word accepted
+    /// only by dfa_a is returned, together with the traces of both automata on that word like in equivalent.
+    #[rpc(name = "is_subset")]
+    fn is_subset(&self, dfa_a: Dfa, dfa_b: Dfa) -> Result<Inclusion>;
+
+    /// Builds the product automaton accepting every word that is accepted by the left or the right Dfa.
+    /// The optional alphabet handling decides what happens if both alphabets differ and defaults to using
+    /// the union of both alphabets. Like minimize, this method also returns a map, which maps every product
+    /// state name to the pair of left and right state names it consists of.
+    #[rpc(name = "union")]
+    fn union(&self, left: Dfa, right: Dfa, alphabet_handling: Option<AlphabetHandling>) -> Result<(Dfa, StatePairsByName)>;
+
+    /// Builds the product automaton accepting every word that is accepted by both Dfas.
+    /// Works exactly like union apart from the accepting states.
+    #[rpc(name = "intersection")]
+    fn intersection(&self, left: Dfa, right: Dfa, alphabet_handling: Option<AlphabetHandling>) -> Result<(Dfa, StatePairsByName)>;
+
+    /// Builds the product automaton accepting every word that is accepted by the left but not by the right Dfa.
+    /// Works exactly like union apart from the accepting states.
+    #[rpc(name = "difference")]
+    fn difference(&self, left: Dfa, right: Dfa, alphabet_handling: Option<AlphabetHandling>) -> Result<(Dfa, StatePairsByName)>;
+
+    /// Builds the product automaton accepting every word that is accepted by exactly one of both Dfas.
+    /// Works exactly like union apart from the accepting states.
+    #[rpc(name = "symmetric_difference")]
+    fn symmetric_difference(&self, left: Dfa, right: Dfa, alphabet_handling: Option<AlphabetHandling>) -> Result<(Dfa, StatePairsByName)>;
+
+    /// Removes all states that cannot be reached from the start state and all dead states, from which no
+    /// accepting state can be reached. Besides the trimmed Dfa, it returns the removed unreachable states and
+    /// the removed dead states, each in alphabetical order. The start state is always kept. Removing dead
+    /// states can leave transitions missing, which means that the trimmed Dfa is not complete anymore.
+    #[rpc(name = "trim")]
+    fn trim(&self, dfa: Dfa) -> Result<(Dfa, Vec<String>, Vec<String>)>;
+
+    /// Adds every missing transition to the Dfa. All of them lead into a fresh trap state, which loops on
+    /// every symbol. The optional alphabet is added to the alphabet of the Dfa before completing it.
+    /// Besides the complete Dfa, the name of the trap state is returned, if one had to be added.
+    #[rpc(name = "complete")]
+    fn complete(&self, dfa: Dfa, alphabet: Option<HashSet<char>>) -> Result<(Dfa, Option<String>)>;
+
+    /// Builds a Dfa accepting exactly the words rejected by the given Dfa. It is completed like in the complete
+    /// method first and then all accepting states become rejecting and vice versa.
+    /// Besides the complement, the name of the trap state is returned, if one had to be added.
+    #[rpc(name = "complement")]
+    fn complement(&self, dfa: Dfa, alphabet: Option<HashSet<char>>) -> Result<(Dfa, Option<String>)>;
+
+    /// Builds an automaton accepting every concatenation of a word accepted by the left and a word accepted by
+    /// the right automaton. Both automata can be a Dfa, an Nfa or an EpsilonNfa. The states of the left automaton
+    /// are prefixed with "1." and those of the right automaton with "2.". The optional output form decides whether
+    /// the constructed EpsilonNfa is returned as it is, which is the default, or converted into a (minimal) Dfa.
+    #[rpc(name = "concat")]
+    fn concat(&self, left: Automaton, right: Automaton, output_form: Option<OutputForm>) -> Result<Automaton>;
+
+    /// Builds an automaton accepting every concatenation of zero or more words accepted by the given automaton.
+    /// Accepts and returns automata like concat does.
+    #[rpc(name = "star")]
+    fn star(&self, automaton: Automaton, output_form: Option<OutputForm>) -> Result<Automaton>;
+
+    /// Builds an automaton accepting every concatenation of one or more words accepted by the given automaton.
+    /// Accepts and returns automata like concat does.
+    #[rpc(name = "plus")]
+    fn plus(&self, automaton: Automaton, output_form: Option<OutputForm>) -> Result<Automaton>;
+
+    /// Builds an automaton accepting the reversal of every word accepted by the given automaton.
+    /// Accepts and returns automata like concat does.
+    #[rpc(name = "reverse")]
+    fn reverse(&self, automaton: Automaton, output_form: Option<OutputForm>) -> Result<Automaton>;
+
+    /// Checks whether the Dfa accepts no word at all. If it accepts a word, a shortest one is returned.
+    #[rpc(name = "is_empty")]
+    fn is_empty(&self, dfa: Dfa) -> Result<Emptiness>;
+
+    /// Checks whether the Dfa accepts only finitely many words. If it accepts infinitely many, a pumpable cycle
+    /// is returned: a prefix, a cycle and a suffix, so that prefix + cycle^n + suffix is accepted for every n.
+    #[rpc(name = "is_finite")]
+    fn is_finite(&self, dfa: Dfa) -> Result<Finiteness>;
+
+    /// Checks whether the Dfa accepts every word over its alphabet. If it does not, a shortest rejected word is returned.
+    #[rpc(name = "is_universal")]
+    fn is_universal(&self, dfa: Dfa) -> Result<Universality>;
+
+    /// Counts the words accepted by the Dfa. The size is returned as a decimal string because it can be arbitrarily
+    /// large. If the language is infinite, there is no size and a pumpable cycle like in is_finite is returned instead.
+    #[rpc(name = "language_size")]
+    fn language_size(&self, dfa: Dfa) -> Result<LanguageSize>;
+
+    /// Lists the words accepted by the Dfa in shortlex order, which orders words by their length first and
+    /// alphabetically second. Only words with at most max_length symbols are listed and at most limit words
+    /// are returned at once. To get the next page, pass the returned next_cursor as cursor.
+    /// Both max_length and limit are bounded, see MAX_ENUMERATED_WORD_LENGTH and MAX_WORDS_PER_PAGE.
+    #[rpc(name = "enumerate_words")]
+    fn enumerate_words(&self, dfa: Dfa, max_length: usize, limit: usize, cursor: Option<String>) -> Result<WordPage>;
+
+    /// Counts the words of exactly the given length that the Dfa accepts.
+    /// The count is returned as a decimal string because it can be arbitrarily large.
+    #[rpc(name = "count_words")]
+    fn count_words(&self, dfa: Dfa, length: usize) -> Result<String>;
+
+    /// Returns a shortest word accepted by the Dfa together with its trace, in the same format check returns it.
+    /// Among all shortest words, the alphabetically smallest one is chosen. Returns null if no word is accepted.
+    #[rpc(name = "shortest_accepted")]
+    fn shortest_accepted(&self, dfa: Dfa) -> Result<Option<(String, Vec<String>)>>;
+
+    /// Returns a shortest word over the alphabet rejected by the Dfa together with its trace, like shortest_accepted.
+    /// If the Dfa has no transition for a symbol of the word, the trace ends with the last state reached.
+    /// Returns null if every word is accepted.
+    #[rpc(name = "shortest_rejected")]
+    fn shortest_rejected(&self, dfa: Dfa) -> Result<Option<(String, Vec<String>)>>;
+
     /// Calls the minimize method of the lammes_automata_theory library crate and improves the output.
     /// The minimize method returns a map with all renaming operations, mapping the old names to the new names.
     /// But for our client it might be more useful to have a list of all old names for each merged new name.
@@ -21,15 +239,188 @@ pub trait Rpc {
     /// new name q0 to all old names, namely q0 and q1.
     #[rpc(name = "minimize")]
     fn minimize(&self, dfa: Dfa) -> Result<(Dfa, HashMap<String, HashSet<String>>)>;
+
+    /// Minimizes the Dfa with the table-filling algorithm and additionally returns its trace:
+    /// every pair of states together with the round in which it has been marked as distinguishable,
+    /// the symbol leading to an already marked pair and a shortest word distinguishing both states.
+    /// Unmarked pairs are equivalent. Missing transitions are completed with a trap state before filling the table.
+    /// The result is built from the table: every class of unmarked states becomes one state, named after the
+    /// alphabetically smallest of its old states, and unreachable classes are removed.
+    #[rpc(name = "minimize_explained")]
+    fn minimize_explained(&self, dfa: Dfa) -> Result<(Dfa, HashMap<String, HashSet<String>>, TableFilling)>;
+
+    /// Minimizes the Dfa with Hopcroft's partition refinement algorithm instead of the lammes_automata_theory
+    /// library crate. Besides the results in the shape of minimize, it returns the initial partition and every
+    /// refining step with its splitter, its symbol and the resulting partition. Unreachable states are removed
+    /// and missing transitions are completed with a trap state first. Every merged state is named after the
+    /// alphabetically smallest of its old states.
+    #[rpc(name = "minimize_hopcroft")]
+    fn minimize_hopcroft(&self, dfa: Dfa) -> Result<(Dfa, HashMap<String, HashSet<String>>, HopcroftTrace)>;
+
+    /// Minimizes the automaton with Brzozowski's algorithm: reverse, determinize, reverse and determinize again.
+    /// In contrast to minimize, the automaton can also be an Nfa or an EpsilonNfa. All intermediate automata are
+    /// returned. The states of each determinized automaton are named after the sets of states they represent.
+    #[rpc(name = "minimize_brzozowski")]
+    fn minimize_brzozowski(&self, automaton: Automaton) -> Result<Brzozowski>;
 }
 
 pub struct RpcImpl;
 impl Rpc for RpcImpl {
+    fn validate(&self, dfa: Value) -> Result<Vec<Diagnostic>> {
+        Ok(validation::validate(&dfa))
+    }
+
     fn check(&self, dfa: Dfa, input: String) -> Result<(bool, Vec<String>)> {
-        Ok(dfa.check(input.as_str()))
+        Ok(validation::validated_with_input(&dfa, &input)?.run(&input))
+    }
+
+    fn nfa_check(&self, nfa: Nfa, input: String) -> Result<(bool, Vec<HashSet<String>>)> {
+        validation::ensure_valid_nfa(&nfa)?;
+        validation::ensure_in_alphabet(&nfa.alphabet, &input)?;
+        Ok(nfa.check(input.as_str()))
+    }
+
+    fn determinize(&self, nfa: Nfa) -> Result<(Dfa, HashMap<String, HashSet<String>>)> {
+        validation::ensure_valid_nfa(&nfa)?;
+        let (dfa, old_names_by_their_new_names) = nfa.determinize()?;
+        Ok((dfa.into_dfa()?, old_names_by_their_new_names))
+    }
+
+    fn epsilon_closure(&self, automaton: EpsilonNfa, state: String) -> Result<HashSet<String>> {
+        validation::ensure_valid_epsilon_nfa(&automaton)?;
+        if !automaton.nfa.states.contains(&state) {
+            return Err(error::unknown_state(&state));
+        }
+        let mut states = HashSet::new();
+        states.insert(state);
+        Ok(automaton.epsilon_closure(&states))
+    }
+
+    fn remove_epsilon(&self, automaton: EpsilonNfa) -> Result<Nfa> {
+        validation::ensure_valid_epsilon_nfa(&automaton)?;
+        Ok(automaton.remove_epsilon())
+    }
+
+    fn regex_to_nfa(&self, regex: String) -> Result<EpsilonNfa> {
+        Ok(Regex::parse(&regex)?.to_epsilon_nfa())
+    }
+
+    fn regex_to_dfa(&self, regex: String) -> Result<Dfa> {
+        let (determinized_nfa, _) = Regex::parse(&regex)?.to_epsilon_nfa().remove_epsilon().determinize()?;
+        let mut dfa = determinized_nfa.into_dfa()?;
+        dfa.minimize();
+        Ok(dfa)
+    }
+
+    fn dfa_to_regex(&self, dfa: Dfa, elimination_order: Option<Vec<String>>, include_steps: Option<bool>) -> Result<StateElimination> {
+        let dfa = validation::validated(&dfa)?;
+        state_elimination::eliminate_states(&dfa, &elimination_order.unwrap_or_default(), include_steps.unwrap_or(false))
+    }
+
+    fn equivalent(&self, dfa_a: Dfa, dfa_b: Dfa) -> Result<Equivalence> {
+        Ok(equivalence::check_equivalence(&validation::validated(&dfa_a)?, &validation::validated(&dfa_b)?))
+    }
+
+    fn is_subset(&self, dfa_a: Dfa, dfa_b: Dfa) -> Result<Inclusion> {
+        Ok(equivalence::check_inclusion(&validation::validated(&dfa_a)?, &validation::validated(&dfa_b)?))
+    }
+
+    fn union(&self, left: Dfa, right: Dfa, alphabet_handling: Option<AlphabetHandling>) -> Result<(Dfa, StatePairsByName)> {
+        product(left, right, ProductOperation::Union, alphabet_handling)
+    }
+
+    fn intersection(&self, left: Dfa, right: Dfa, alphabet_handling: Option<AlphabetHandling>) -> Result<(Dfa, StatePairsByName)> {
+        product(left, right, ProductOperation::Intersection, alphabet_handling)
+    }
+
+    fn difference(&self, left: Dfa, right: Dfa, alphabet_handling: Option<AlphabetHandling>) -> Result<(Dfa, StatePairsByName)> {
+        product(left, right, ProductOperation::Difference, alphabet_handling)
+    }
+
+    fn symmetric_difference(&self, left: Dfa, right: Dfa, alphabet_handling: Option<AlphabetHandling>) -> Result<(Dfa, StatePairsByName)> {
+        product(left, right, ProductOperation::SymmetricDifference, alphabet_handling)
+    }
+
+    fn trim(&self, dfa: Dfa) -> Result<(Dfa, Vec<String>, Vec<String>)> {
+        let mut dfa = validation::validated(&dfa)?;
+        let (unreachable_states, dead_states) = dfa.trim();
+        Ok((dfa.into_dfa()?, unreachable_states, dead_states))
+    }
+
+    fn complete(&self, dfa: Dfa, alphabet: Option<HashSet<char>>) -> Result<(Dfa, Option<String>)> {
+        let mut dfa = validation::validated(&dfa)?;
+        let trap_state = dfa.complete(&alphabet.unwrap_or_default());
+        Ok((dfa.into_dfa()?, trap_state))
+    }
+
+    fn complement(&self, dfa: Dfa, alphabet: Option<HashSet<char>>) -> Result<(Dfa, Option<String>)> {
+        let mut dfa = validation::validated(&dfa)?;
+        let trap_state = dfa.complement(&alphabet.unwrap_or_default());
+        Ok((dfa.into_dfa()?, trap_state))
+    }
+
+    fn concat(&self, left: Automaton, right: Automaton, output_form: Option<OutputForm>) -> Result<Automaton> {
+        let concatenation = regular_operations::concatenation(&left.into_epsilon_nfa()?, &right.into_epsilon_nfa()?);
+        Automaton::from_construction(concatenation, output_form.unwrap_or(OutputForm::EpsilonNfa))
+    }
+
+    fn star(&self, automaton: Automaton, output_form: Option<OutputForm>) -> Result<Automaton> {
+        let star = regular_operations::star(&automaton.into_epsilon_nfa()?);
+        Automaton::from_construction(star, output_form.unwrap_or(OutputForm::EpsilonNfa))
+    }
+
+    fn plus(&self, automaton: Automaton, output_form: Option<OutputForm>) -> Result<Automaton> {
+        let plus = regular_operations::plus(&automaton.into_epsilon_nfa()?);
+        Automaton::from_construction(plus, output_form.unwrap_or(OutputForm::EpsilonNfa))
+    }
+
+    fn reverse(&self, automaton: Automaton, output_form: Option<OutputForm>) -> Result<Automaton> {
+        let reversed = regular_operations::reverse(&automaton.into_epsilon_nfa()?);
+        Automaton::from_construction(reversed, output_form.unwrap_or(OutputForm::EpsilonNfa))
+    }
+
+    fn is_empty(&self, dfa: Dfa) -> Result<Emptiness> {
+        Ok(decision::check_emptiness(&validation::validated(&dfa)?))
+    }
+
+    fn is_finite(&self, dfa: Dfa) -> Result<Finiteness> {
+        Ok(decision::check_finiteness(&validation::validated(&dfa)?))
+    }
+
+    fn is_universal(&self, dfa: Dfa) -> Result<Universality> {
+        Ok(decision::check_universality(&validation::validated(&dfa)?))
+    }
+
+    fn language_size(&self, dfa: Dfa) -> Result<LanguageSize> {
+        Ok(decision::count_language(&validation::validated(&dfa)?))
+    }
+
+    fn enumerate_words(&self, dfa: Dfa, max_length: usize, limit: usize, cursor: Option<String>) -> Result<WordPage> {
+        enumeration::enumerate_words(&validation::validated(&dfa)?, max_length, limit, cursor.as_deref())
+    }
+
+    fn count_words(&self, dfa: Dfa, length: usize) -> Result<String> {
+        Ok(enumeration::count_words(&validation::validated(&dfa)?, length)?.to_string())
+    }
+
+    fn shortest_accepted(&self, dfa: Dfa) -> Result<Option<(String, Vec<String>)>> {
+        let dfa = validation::validated(&dfa)?;
+        Ok(decision::shortest_accepted_word(&dfa).map(|word| {
+            let (_, trace) = dfa.run(&word);
+            (word, trace)
+        }))
+    }
+
+    fn shortest_rejected(&self, dfa: Dfa) -> Result<Option<(String, Vec<String>)>> {
+        let dfa = validation::validated(&dfa)?;
+        Ok(decision::shortest_rejected_word(&dfa).map(|word| {
+            let (_, trace) = dfa.run(&word);
+            (word, trace)
+        }))
     }
 
     fn minimize(&self, dfa: Dfa) -> Result<(Dfa, HashMap<String, HashSet<String>>)> {
+        validation::validated(&dfa)?;
         let mut minimized_dfa = dfa.clone();
         let renaming_operations = minimized_dfa.minimize();
         // The renaming_operations maps every old name to the new name.
@@ -51,18 +442,104 @@ impl Rpc for RpcImpl {
         }
         Ok((minimized_dfa, old_names_by_their_new_names))
     }
+
+    fn minimize_explained(&self, dfa: Dfa) -> Result<(Dfa, HashMap<String, HashSet<String>>, TableFilling)> {
+        let (minimized_dfa, old_names_by_their_new_names, table_filling) =
+            table_filling::minimize(&validation::validated(&dfa)?);
+        Ok((minimized_dfa.into_dfa()?, old_names_by_their_new_names, table_filling))
+    }
+
+    fn minimize_hopcroft(&self, dfa: Dfa) -> Result<(Dfa, HashMap<String, HashSet<String>>, HopcroftTrace)> {
+        let (minimized_dfa, old_names_by_their_new_names, trace) = hopcroft::minimize(&validation::validated(&dfa)?);
+        Ok((minimized_dfa.into_dfa()?, old_names_by_their_new_names, trace))
+    }
+
+    fn minimize_brzozowski(&self, automaton: Automaton) -> Result<Brzozowski> {
+        brzozowski::minimize(&automaton.into_epsilon_nfa()?)
+    }
+}
+
+/// Builds a product automaton of two Dfas that have been passed by a client.
+fn product(left: Dfa, right: Dfa, operation: ProductOperation, alphabet_handling: Option<AlphabetHandling>)
+           -> Result<(Dfa, StatePairsByName)> {
+    let (product, pairs_by_name) = product::product(
+        &validation::validated(&left)?,
+        &validation::validated(&right)?,
+        operation,
+        alphabet_handling.unwrap_or(AlphabetHandling::Union),
+    )?;
+    Ok((product.into_dfa()?, pairs_by_name))
 }
 
 /// Starts a server that exposes the functionality of the [lammes_automata_theory library crate](https://github.com/simon-lammes/lammes_automata_theory)
-/// via HTTP, using the JSON-RCP specifications. The server library can be
-/// found [here.](https://github.com/paritytech/jsonrpc)
+/// via HTTP and WebSocket, and optionally via raw TCP and IPC, using the JSON-RCP specifications.
+/// The server library can be found [here.](https://github.com/paritytech/jsonrpc)
+/// Only the WebSocket server offers the subscriptions, because HTTP cannot push notifications.
+/// With --stdio, no port is opened and the requests are read from stdin instead.
+/// The address, ports, IPC path, thread count and CORS settings are configurable, see config::USAGE.
 fn main() {
-    let mut io = IoHandler::new();
+    let config = match config::load(env::args().skip(1)) {
+        Ok(Some(config)) => config,
+        Ok(None) => {
+            println!("{}", config::USAGE);
+            return;
+        },
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, config::USAGE);
+            process::exit(2);
+        }
+    };
+    // Every call is guarded, so that a panicking procedure cannot take down a worker thread.
+    let mut io: MetaIoHandler<(), PanicGuard> = MetaIoHandler::with_middleware(PanicGuard);
     // Register the procedures that should be callable via RPC.
     io.extend_with(RpcImpl.to_delegate());
-    let server = ServerBuilder::new(io)
-        .threads(3)
-        .start_http(&"127.0.0.1:3030".parse().unwrap())
-        .unwrap();
+    if config.stdio {
+        if let Err(error) = stdio::serve(&io, std::io::stdin().lock(), std::io::stdout().lock()) {
+            exit_with_error("Could not serve on stdin and stdout", error);
+        }
+        return;
+    }
+    // The optional servers share the handler and run until they are dropped at the end of main.
+    let _tcp_server = config.tcp_port.map(|port| {
+        jsonrpc_tcp_server::ServerBuilder::new(io.clone())
+            .start(&SocketAddr::new(config.address, port))
+            .unwrap_or_else(|error| exit_with_error("Could not start the TCP server", error))
+    });
+    let _ipc_server = config.ipc_path.map(|path| {
+        jsonrpc_ipc_server::ServerBuilder::new(io.clone())
+            .start(&path)
+            .unwrap_or_else(|error| exit_with_error(&format!("Could not start the IPC server at {}", path), error))
+    });
+    let mut server_builder = ServerBuilder::new(io)
+        .threads(config.threads)
+        .cors_max_age(config.cors_max_age);
+    if let Some(origins) = config.cors_allowed_origins {
+        let origins = origins.into_iter().map(AccessControlAllowOrigin::from).collect();
+        server_builder = server_builder.cors(DomainsValidation::AllowOnly(origins));
+    }
+    if let Some(headers) = config.cors_allowed_headers {
+        server_builder = server_builder.cors_allow_headers(AccessControlAllowHeaders::Only(headers));
+    }
+    let server = server_builder
+        .start_http(&SocketAddr::new(config.address, config.port))
+        .unwrap_or_else(|error| exit_with_error("Could not start the HTTP server", error));
+    let mut websocket_io = PubSubHandler::new(MetaIoHandler::with_middleware(PanicGuard));
+    websocket_io.extend_with(RpcImpl.to_delegate());
+    websocket_io.extend_with(SimulationImpl::default().to_delegate());
+    let websocket_server = jsonrpc_ws_server::ServerBuilder::with_meta_extractor(
+        websocket_io,
+        |context: &jsonrpc_ws_server::RequestContext| Arc::new(Session::new(context.sender())),
+    )
+        .start(&SocketAddr::new(config.address, config.websocket_port))
+        .unwrap_or_else(|error| exit_with_error("Could not start the WebSocket server", error));
     server.wait();
+    if let Err(error) = websocket_server.wait() {
+        exit_with_error("The WebSocket server failed", error);
+    }
+}
+
+/// Reports an error that prevents the server from running and exits.
+fn exit_with_error(context: &str, error: impl fmt::Display) -> ! {
+    eprintln!("{}: {}", context, error);
+    process::exit(1);
 }
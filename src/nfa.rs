@@ -0,0 +1,153 @@
+use crate::dfa_model::{DfaModel, StateNames};
+use crate::error;
+use jsonrpc_core::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+/// The subset construction can create exponentially many states, so it is aborted beyond this number.
+pub const MAX_SUBSET_CONSTRUCTION_STATES: usize = 10_000;
+
+/// A nondeterministic finite automaton. In contrast to the Dfa of the lammes_automata_theory library crate,
+/// a state can have multiple targets for the same symbol and the automaton can have multiple start states.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Nfa {
+    pub alphabet: HashSet<char>,
+    pub states: HashSet<String>,
+    pub start_states: HashSet<String>,
+    pub accept_states: HashSet<String>,
+    /// Maps every state to all the states it can move to for each symbol.
+    /// A missing entry means that the state has no targets for that symbol.
+    #[serde(default)]
+    pub transitions: HashMap<String, HashMap<char, HashSet<String>>>,
+}
+
+impl Nfa {
+    /// Returns whether the input is accepted and the set of reachable states for every input position.
+    /// The first set contains the start states, every further set contains the states that are reachable
+    /// after consuming one more symbol. The input is accepted if the last set contains an accepting state.
+    pub fn check(&self, input: &str) -> (bool, Vec<HashSet<String>>) {
+        let mut current_states = self.start_states.clone();
+        let mut reachable_states_per_position = vec![current_states.clone()];
+        for symbol in input.chars() {
+            current_states = self.step(&current_states, symbol);
+            reachable_states_per_position.push(current_states.clone());
+        }
+        let accepted = current_states.iter().any(|state| self.accept_states.contains(state));
+        (accepted, reachable_states_per_position)
+    }
+
+    /// Returns all states that are reachable from any of the given states by consuming the symbol.
+    pub fn step(&self, states: &HashSet<String>, symbol: char) -> HashSet<String> {
+        states.iter()
+            .filter_map(|state| self.transitions.get(state))
+            .filter_map(|targets_by_symbol| targets_by_symbol.get(&symbol))
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    /// Converts this automaton into a deterministic one using the subset construction.
+    /// Only the subsets that are reachable from the start states are constructed, which may include the empty set.
+    /// Besides the deterministic automaton, a map is returned that maps every new state name to the set of
+    /// states of this automaton that the new state represents.
+    pub fn determinize(&self) -> Result<(DfaModel, HashMap<String, HashSet<String>>)> {
+        let mut alphabet: Vec<char> = self.alphabet.iter().cloned().collect();
+        alphabet.sort();
+        let start_subset: BTreeSet<String> = self.start_states.iter().cloned().collect();
+        let mut names = StateNames::default();
+        let (start_state, _) = names.name(&start_subset, subset_name(&start_subset));
+        let mut dfa = DfaModel {
+            alphabet: self.alphabet.clone(),
+            states: HashSet::new(),
+            start_state: start_state.clone(),
+            accept_states: HashSet::new(),
+            transitions: HashMap::new(),
+        };
+        let mut old_names_by_their_new_names: HashMap<String, HashSet<String>> = HashMap::new();
+        let mut unprocessed_subsets = VecDeque::new();
+        unprocessed_subsets.push_back((start_subset, start_state));
+        while let Some((subset, name)) = unprocessed_subsets.pop_front() {
+            if dfa.states.len() >= MAX_SUBSET_CONSTRUCTION_STATES {
+                return Err(error::resource_limit_exceeded("states of the subset construction", MAX_SUBSET_CONSTRUCTION_STATES));
+            }
+            dfa.states.insert(name.clone());
+            if subset.iter().any(|state| self.accept_states.contains(state)) {
+                dfa.accept_states.insert(name.clone());
+            }
+            let subset_as_set: HashSet<String> = subset.iter().cloned().collect();
+            let mut targets_by_symbol = HashMap::new();
+            for &symbol in &alphabet {
+                let target_subset: BTreeSet<String> = self.step(&subset_as_set, symbol).into_iter().collect();
+                let (target_name, is_new) = names.name(&target_subset, subset_name(&target_subset));
+                targets_by_symbol.insert(symbol, target_name.clone());
+                if is_new {
+                    unprocessed_subsets.push_back((target_subset, target_name));
+                }
+            }
+            dfa.transitions.insert(name.clone(), targets_by_symbol);
+            old_names_by_their_new_names.insert(name, subset_as_set);
+        }
+        Ok((dfa, old_names_by_their_new_names))
+    }
+}
+
+/// Names a state of the subset construction after the states it contains, e.g. "{q0,q1}".
+/// This name can clash with the one of another subset if the states contain commas.
+fn subset_name(subset: &BTreeSet<String>) -> String {
+    format!("{{{}}}", subset.iter().cloned().collect::<Vec<String>>().join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{nfa, set};
+    use serde_json::json;
+
+    #[test]
+    fn determinize_constructs_the_reachable_subsets() {
+        // Accepts all words ending in ab.
+        let nfa = nfa(json!({
+            "alphabet": ["a", "b"], "states": ["q0", "q1", "q2"], "start_states": ["q0"], "accept_states": ["q2"],
+            "transitions": {"q0": {"a": ["q0", "q1"], "b": ["q0"]}, "q1": {"b": ["q2"]}}
+        }));
+        let (dfa, old_names_by_their_new_names) = nfa.determinize().unwrap();
+        assert_eq!(dfa.states, set(&["{q0}", "{q0,q1}", "{q0,q2}"]));
+        assert_eq!(dfa.start_state, "{q0}");
+        assert_eq!(dfa.accept_states, set(&["{q0,q2}"]));
+        assert_eq!(dfa.target("{q0,q1}", 'b').unwrap(), "{q0,q2}");
+        assert_eq!(dfa.target("{q0,q2}", 'a').unwrap(), "{q0,q1}");
+        assert_eq!(old_names_by_their_new_names["{q0,q1}"], set(&["q0", "q1"]));
+        assert!(dfa.run("abab").0);
+        assert!(!dfa.run("aba").0);
+    }
+
+    #[test]
+    fn determinize_keeps_subsets_with_clashing_names_apart() {
+        let nfa = nfa(json!({
+            "alphabet": ["x"], "states": ["a,b", "c", "a", "b,c"], "start_states": ["a,b", "c"], "accept_states": ["a"],
+            "transitions": {"c": {"x": ["a", "b,c"]}}
+        }));
+        let (dfa, old_names_by_their_new_names) = nfa.determinize().unwrap();
+        assert_eq!(dfa.states, set(&["{a,b,c}", "{a,b,c}'", "{}"]));
+        assert_eq!(old_names_by_their_new_names["{a,b,c}"], set(&["a,b", "c"]));
+        assert_eq!(old_names_by_their_new_names["{a,b,c}'"], set(&["a", "b,c"]));
+        assert_eq!(dfa.accept_states, set(&["{a,b,c}'"]));
+    }
+
+    #[test]
+    fn determinize_aborts_beyond_the_state_limit() {
+        // The n-th last symbol is an a, which needs 2^n subsets.
+        let n = 14;
+        let mut transitions = json!({"q0": {"a": ["q0", "q1"], "b": ["q0"]}});
+        for i in 1..n {
+            transitions[format!("q{}", i)] = json!({"a": [format!("q{}", i + 1)], "b": [format!("q{}", i + 1)]});
+        }
+        let states: Vec<String> = (0..=n).map(|i| format!("q{}", i)).collect();
+        let nfa = nfa(json!({
+            "alphabet": ["a", "b"], "states": states, "start_states": ["q0"], "accept_states": [format!("q{}", n)],
+            "transitions": transitions
+        }));
+        let error = nfa.determinize().unwrap_err();
+        assert_eq!(error.code, jsonrpc_core::ErrorCode::ServerError(error::RESOURCE_LIMIT_EXCEEDED));
+    }
+}
@@ -0,0 +1,87 @@
+use crate::error;
+use jsonrpc_core::futures::future::{self, Either};
+use jsonrpc_core::futures::Future;
+use jsonrpc_core::{Call, Id, Metadata, Middleware, Output, Response, Version};
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+
+/// Catches panics of the called procedures, e.g. inside the lammes_automata_theory library crate, and turns
+/// them into error responses. Without it, a panic would unwind through the worker thread of the server.
+/// The panic is still printed by the panic hook, so it shows up in the logs.
+#[derive(Clone)]
+pub struct PanicGuard;
+
+impl<M: Metadata> Middleware<M> for PanicGuard {
+    type Future = Box<dyn Future<Item = Option<Response>, Error = ()> + Send>;
+    type CallFuture = Box<dyn Future<Item = Option<Output>, Error = ()> + Send>;
+
+    fn on_call<F, X>(&self, call: Call, meta: M, next: F) -> Either<Self::CallFuture, X>
+        where F: Fn(Call, M) -> X + Send + Sync,
+              X: Future<Item = Option<Output>, Error = ()> + Send + 'static {
+        // Notifications do not get a response, not even an error.
+        let id_and_version = match &call {
+            Call::MethodCall(method_call) => Some((method_call.id.clone(), method_call.jsonrpc)),
+            Call::Notification(_) | Call::Invalid { .. } => None,
+        };
+        // Notifications run right away, while methods only run once the returned future is polled,
+        // so both the call and the future need guarding.
+        let future = match panic::catch_unwind(AssertUnwindSafe(|| next(call, meta))) {
+            Ok(future) => future,
+            Err(payload) => return Either::A(Box::new(future::ok(panicked_output(id_and_version, payload)))),
+        };
+        let guarded_future = AssertUnwindSafe(future)
+            .catch_unwind()
+            .then(move |result| match result {
+                Ok(output) => output,
+                Err(payload) => Ok(panicked_output(id_and_version, payload)),
+            });
+        Either::A(Box::new(guarded_future))
+    }
+}
+
+/// Builds the error response for a panicked call, which is none for notifications.
+fn panicked_output(id_and_version: Option<(Id, Option<Version>)>, payload: Box<dyn Any + Send>) -> Option<Output> {
+    id_and_version.map(|(id, version)| Output::from(Err(error::panicked(&panic_message(payload.as_ref()))), id, version))
+}
+
+/// Panics carry either a static string or a formatted string, depending on how panic! has been called.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        String::from("unknown panic")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpc_core::{MetaIoHandler, Params, Value};
+    use serde_json::json;
+
+    fn handler() -> MetaIoHandler<(), PanicGuard> {
+        let mut handler = MetaIoHandler::with_middleware(PanicGuard);
+        handler.add_method("explode", |_: Params| -> jsonrpc_core::Result<Value> { panic!("boom {}", 42) });
+        handler.add_notification("explode_quietly", |_: Params| panic!("boom"));
+        handler.add_method("ping", |_: Params| Ok(Value::from("pong")));
+        handler
+    }
+
+    fn response(handler: &MetaIoHandler<(), PanicGuard>, request: Value) -> Option<Value> {
+        handler.handle_request_sync(&request.to_string(), ()).map(|response| serde_json::from_str(&response).unwrap())
+    }
+
+    #[test]
+    fn panics_become_error_responses_and_the_handler_keeps_serving() {
+        let handler = handler();
+        let panicked = response(&handler, json!({"jsonrpc": "2.0", "id": 7, "method": "explode"})).unwrap();
+        assert_eq!(panicked["id"], 7);
+        assert_eq!(panicked["error"]["code"], error::PANICKED);
+        assert_eq!(panicked["error"]["data"]["panic"], "boom 42");
+        assert_eq!(response(&handler, json!({"jsonrpc": "2.0", "method": "explode_quietly"})), None);
+        let answered = response(&handler, json!({"jsonrpc": "2.0", "id": 8, "method": "ping"})).unwrap();
+        assert_eq!(answered, json!({"jsonrpc": "2.0", "id": 8, "result": "pong"}));
+    }
+}
@@ -0,0 +1,175 @@
+use crate::dfa_model::{DfaModel, StateNames};
+use crate::error;
+use jsonrpc_core::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Maps every product state name to the pair of left and right state names it consists of.
+pub type StatePairsByName = HashMap<String, (String, String)>;
+
+/// Determines the alphabet of a product automaton if the alphabets of both automata differ.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum AlphabetHandling {
+    /// Uses all symbols of both alphabets. A symbol that one automaton does not know leads it into a trap state.
+    Union,
+    /// Uses only the symbols that both automata know. Transitions for all other symbols are dropped.
+    Intersection,
+    /// Refuses to build the product if the alphabets differ.
+    Strict,
+}
+
+/// The set operation that a product automaton implements on the languages of both automata.
+#[derive(Clone, Copy, Debug)]
+pub enum ProductOperation {
+    Union,
+    Intersection,
+    Difference,
+    SymmetricDifference,
+}
+
+impl ProductOperation {
+    fn accepts(self, accepted_by_left: bool, accepted_by_right: bool) -> bool {
+        match self {
+            ProductOperation::Union => accepted_by_left || accepted_by_right,
+            ProductOperation::Intersection => accepted_by_left && accepted_by_right,
+            ProductOperation::Difference => accepted_by_left && !accepted_by_right,
+            ProductOperation::SymmetricDifference => accepted_by_left != accepted_by_right,
+        }
+    }
+}
+
+/// Builds the product automaton of both Dfas that accepts the language described by the operation.
+/// Only the state pairs reachable from the pair of start states are constructed. Every product state is named
+/// after its pair, e.g. "(q0,p1)", with apostrophes appended if the names of two pairs clash. Besides the
+/// product, a map is returned that maps every product state name to its pair of left and right state.
+/// Both automata are completed first, so a pair can contain a trap state.
+pub fn product(left: &DfaModel, right: &DfaModel, operation: ProductOperation, alphabet_handling: AlphabetHandling)
+               -> Result<(DfaModel, StatePairsByName)> {
+    let alphabet: HashSet<char> = match alphabet_handling {
+        AlphabetHandling::Union => left.alphabet.union(&right.alphabet).cloned().collect(),
+        AlphabetHandling::Intersection => left.alphabet.intersection(&right.alphabet).cloned().collect(),
+        AlphabetHandling::Strict => {
+            if left.alphabet != right.alphabet {
+                let mut left_only: Vec<char> = left.alphabet.difference(&right.alphabet).cloned().collect();
+                let mut right_only: Vec<char> = right.alphabet.difference(&left.alphabet).cloned().collect();
+                left_only.sort();
+                right_only.sort();
+                return Err(error::incompatible_alphabets(left_only, right_only));
+            }
+            left.alphabet.clone()
+        }
+    };
+    let left = restricted_and_completed(left, &alphabet);
+    let right = restricted_and_completed(right, &alphabet);
+    let mut sorted_alphabet: Vec<char> = alphabet.iter().cloned().collect();
+    sorted_alphabet.sort();
+
+    let start_pair = (left.start_state.clone(), right.start_state.clone());
+    let mut names = StateNames::default();
+    let (start_state, _) = names.name(&start_pair, pair_name(&start_pair));
+    let mut product = DfaModel {
+        alphabet,
+        states: HashSet::new(),
+        start_state: start_state.clone(),
+        accept_states: HashSet::new(),
+        transitions: HashMap::new(),
+    };
+    let mut pairs_by_name = HashMap::new();
+    let mut unprocessed_pairs = VecDeque::new();
+    unprocessed_pairs.push_back((start_pair, start_state));
+    while let Some((pair, name)) = unprocessed_pairs.pop_front() {
+        product.states.insert(name.clone());
+        if operation.accepts(left.accept_states.contains(&pair.0), right.accept_states.contains(&pair.1)) {
+            product.accept_states.insert(name.clone());
+        }
+        let mut targets_by_symbol = HashMap::new();
+        for &symbol in &sorted_alphabet {
+            // Both automata are complete, so there always is a target.
+            let target_pair = (
+                left.target(&pair.0, symbol).unwrap().clone(),
+                right.target(&pair.1, symbol).unwrap().clone(),
+            );
+            let (target_name, is_new) = names.name(&target_pair, pair_name(&target_pair));
+            targets_by_symbol.insert(symbol, target_name.clone());
+            if is_new {
+                unprocessed_pairs.push_back((target_pair, target_name));
+            }
+        }
+        product.transitions.insert(name.clone(), targets_by_symbol);
+        pairs_by_name.insert(name, pair);
+    }
+    Ok((product, pairs_by_name))
+}
+
+/// Returns a copy of the automaton that has exactly the given alphabet and no missing transitions.
+fn restricted_and_completed(dfa: &DfaModel, alphabet: &HashSet<char>) -> DfaModel {
+    let mut dfa = dfa.clone();
+    dfa.alphabet.retain(|symbol| alphabet.contains(symbol));
+    for targets_by_symbol in dfa.transitions.values_mut() {
+        targets_by_symbol.retain(|symbol, _| alphabet.contains(symbol));
+    }
+    dfa.complete(alphabet);
+    dfa
+}
+
+/// Names a state of the product after the pair of states it represents, e.g. "(q0,p1)".
+/// This name can clash with the one of another pair if the states contain commas.
+fn pair_name(pair: &(String, String)) -> String {
+    format!("({},{})", pair.0, pair.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::dfa;
+    use serde_json::json;
+
+    #[test]
+    fn intersection_accepts_words_accepted_by_both() {
+        // Words with an even number of a and words ending in b.
+        let even_a = dfa(json!({
+            "alphabet": ["a", "b"], "states": ["e", "o"], "start_state": "e", "accept_states": ["e"],
+            "transitions": {"e": {"a": "o", "b": "e"}, "o": {"a": "e", "b": "o"}}
+        }));
+        let ends_in_b = dfa(json!({
+            "alphabet": ["a", "b"], "states": ["x", "y"], "start_state": "x", "accept_states": ["y"],
+            "transitions": {"x": {"a": "x", "b": "y"}, "y": {"a": "x", "b": "y"}}
+        }));
+        let (product, pairs_by_name) =
+            product(&even_a, &ends_in_b, ProductOperation::Intersection, AlphabetHandling::Strict).unwrap();
+        assert_eq!(product.states.len(), 4);
+        assert_eq!(product.start_state, "(e,x)");
+        assert_eq!(pairs_by_name["(o,y)"], ("o".to_string(), "y".to_string()));
+        for (word, accepted) in [("aab", true), ("ab", false), ("aa", false), ("b", true)] {
+            assert_eq!(product.run(word).0, accepted, "{}", word);
+        }
+    }
+
+    #[test]
+    fn strict_alphabet_handling_rejects_different_alphabets() {
+        let left = dfa(json!({"alphabet": ["a"], "states": ["q"], "start_state": "q", "accept_states": []}));
+        let right = dfa(json!({"alphabet": ["b"], "states": ["q"], "start_state": "q", "accept_states": []}));
+        let error = product(&left, &right, ProductOperation::Union, AlphabetHandling::Strict).unwrap_err();
+        assert_eq!(error.data, Some(json!({ "left_only": ["a"], "right_only": ["b"] })));
+    }
+
+    #[test]
+    fn keeps_pairs_with_clashing_names_apart() {
+        let left = dfa(json!({
+            "alphabet": ["x"], "states": ["a,b", "a"], "start_state": "a,b", "accept_states": ["a"],
+            "transitions": {"a,b": {"x": "a"}, "a": {"x": "a"}}
+        }));
+        let right = dfa(json!({
+            "alphabet": ["x"], "states": ["c", "b,c"], "start_state": "c", "accept_states": [],
+            "transitions": {"c": {"x": "b,c"}, "b,c": {"x": "b,c"}}
+        }));
+        let (product, pairs_by_name) =
+            product(&left, &right, ProductOperation::Union, AlphabetHandling::Strict).unwrap();
+        assert_eq!(product.states.len(), 2);
+        assert_eq!(pairs_by_name["(a,b,c)"], ("a,b".to_string(), "c".to_string()));
+        assert_eq!(pairs_by_name["(a,b,c)'"], ("a".to_string(), "b,c".to_string()));
+        assert!(product.run("x").0);
+        assert!(!product.run("").0);
+    }
+}
@@ -0,0 +1,448 @@
+use crate::epsilon_nfa::EpsilonNfa;
+use crate::error;
+use crate::nfa::Nfa;
+use jsonrpc_core::Result;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// A regular expression in textbook notation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Regex {
+    /// Describes the empty language, written as ∅.
+    EmptySet,
+    /// Describes the language that only contains the empty word, written as ε.
+    Epsilon,
+    Symbol(char),
+    /// Written as r|s or r+s.
+    Union(Box<Regex>, Box<Regex>),
+    /// Written as rs.
+    Concatenation(Box<Regex>, Box<Regex>),
+    /// Written as r*.
+    Star(Box<Regex>),
+}
+
+/// The maximum number of characters of a parsed regular expression. Parsing, compiling and writing a regular
+/// expression recurse along its syntax tree, which gets about as deep as the expression is long.
+pub const MAX_REGEX_LENGTH: usize = 1_000;
+/// The maximum number of parentheses that can be open at the same time.
+pub const MAX_NESTING_DEPTH: usize = 100;
+
+impl Regex {
+    /// Parses a regular expression in textbook notation. Union is written as | or +, concatenation as
+    /// juxtaposition and the Kleene star as *. Stars bind stronger than concatenation, which binds stronger
+    /// than union. Parentheses can be used for grouping, ε denotes the empty word and ∅ the empty language.
+    /// Every other character, except for whitespace which is ignored, is a symbol of the alphabet. A backslash
+    /// turns the character after it into a symbol, even if it is an operator, ε, ∅, whitespace or a backslash.
+    /// The positions in the errors count the characters, not the bytes, of the regular expression.
+    pub fn parse(regex: &str) -> Result<Regex> {
+        if regex.chars().count() > MAX_REGEX_LENGTH {
+            return Err(error::resource_limit_exceeded("characters of a regular expression", MAX_REGEX_LENGTH));
+        }
+        let mut parser = Parser { chars: regex.chars().peekable(), position: 0, depth: 0 };
+        let parsed_regex = parser.parse_union()?;
+        match parser.peek() {
+            None => Ok(parsed_regex),
+            Some(unexpected) => Err(parser.error(format!("Unexpected {}.", unexpected))),
+        }
+    }
+
+    /// Creates the union of both regular expressions, simplifying it where this is trivially possible.
+    /// The constructors union, concatenation and star keep regular expressions built by algorithms
+    /// like the state elimination readable.
+    pub fn union(left: Regex, right: Regex) -> Regex {
+        match (left, right) {
+            (Regex::EmptySet, other) | (other, Regex::EmptySet) => other,
+            (left, right) if left == right => left,
+            (left, right) => Regex::Union(Box::new(left), Box::new(right))
+        }
+    }
+
+    /// Creates the concatenation of both regular expressions, simplifying it where this is trivially possible.
+    pub fn concatenation(left: Regex, right: Regex) -> Regex {
+        match (left, right) {
+            (Regex::EmptySet, _) | (_, Regex::EmptySet) => Regex::EmptySet,
+            (Regex::Epsilon, other) | (other, Regex::Epsilon) => other,
+            (left, right) => Regex::Concatenation(Box::new(left), Box::new(right))
+        }
+    }
+
+    /// Creates the Kleene star of the regular expression, simplifying it where this is trivially possible.
+    pub fn star(inner: Regex) -> Regex {
+        match inner {
+            Regex::EmptySet | Regex::Epsilon => Regex::Epsilon,
+            Regex::Star(_) => inner,
+            inner => Regex::Star(Box::new(inner))
+        }
+    }
+
+    /// Returns the number of nodes of the syntax tree and its depth, which is 1 for a single node.
+    /// The tree is traversed with an explicit stack, so that even regular expressions too deep to be written
+    /// can be measured.
+    pub fn size_and_depth(&self) -> (usize, usize) {
+        let mut size = 0;
+        let mut depth = 0;
+        let mut unvisited_nodes = vec![(self, 1)];
+        while let Some((node, node_depth)) = unvisited_nodes.pop() {
+            size += 1;
+            depth = depth.max(node_depth);
+            match node {
+                Regex::Union(left, right) | Regex::Concatenation(left, right) => {
+                    unvisited_nodes.push((left, node_depth + 1));
+                    unvisited_nodes.push((right, node_depth + 1));
+                },
+                Regex::Star(inner) => unvisited_nodes.push((inner, node_depth + 1)),
+                Regex::EmptySet | Regex::Epsilon | Regex::Symbol(_) => {}
+            }
+        }
+        (size, depth)
+    }
+
+    /// The higher the precedence, the stronger the operator binds. Symbols, ε and ∅ never need parentheses.
+    fn precedence(&self) -> u8 {
+        match self {
+            Regex::Union(_, _) => 0,
+            Regex::Concatenation(_, _) => 1,
+            Regex::Star(_) => 2,
+            Regex::EmptySet | Regex::Epsilon | Regex::Symbol(_) => 3
+        }
+    }
+
+    /// Writes the regular expression, wrapping it in parentheses if it binds weaker than its context requires.
+    fn fmt_with_precedence(&self, f: &mut fmt::Formatter, required_precedence: u8) -> fmt::Result {
+        if self.precedence() < required_precedence {
+            write!(f, "(")?;
+            self.fmt_with_precedence(f, 0)?;
+            return write!(f, ")");
+        }
+        match self {
+            Regex::EmptySet => write!(f, "∅"),
+            Regex::Epsilon => write!(f, "ε"),
+            Regex::Symbol(symbol) if needs_escaping(*symbol) => write!(f, "\\{}", symbol),
+            Regex::Symbol(symbol) => write!(f, "{}", symbol),
+            Regex::Union(left, right) => {
+                left.fmt_with_precedence(f, 0)?;
+                write!(f, "|")?;
+                right.fmt_with_precedence(f, 0)
+            },
+            Regex::Concatenation(left, right) => {
+                left.fmt_with_precedence(f, 1)?;
+                right.fmt_with_precedence(f, 1)
+            },
+            Regex::Star(inner) => {
+                inner.fmt_with_precedence(f, 3)?;
+                write!(f, "*")
+            }
+        }
+    }
+
+    /// Compiles this regular expression into an automaton with epsilon transitions using the Thompson construction.
+    /// The states are named q0, q1, ... in the order they are created. The automaton has exactly one start
+    /// and one accepting state and its alphabet consists of the symbols occurring in the regular expression.
+    pub fn to_epsilon_nfa(&self) -> EpsilonNfa {
+        let mut construction = ThompsonConstruction {
+            automaton: EpsilonNfa {
+                nfa: Nfa {
+                    alphabet: HashSet::new(),
+                    states: HashSet::new(),
+                    start_states: HashSet::new(),
+                    accept_states: HashSet::new(),
+                    transitions: HashMap::new(),
+                },
+                epsilon_transitions: HashMap::new(),
+            },
+        };
+        let (start_state, accept_state) = construction.build(self);
+        let mut automaton = construction.automaton;
+        automaton.nfa.start_states.insert(start_state);
+        automaton.nfa.accept_states.insert(accept_state);
+        automaton
+    }
+}
+
+/// Writes the regular expression in the same textbook notation that Regex::parse accepts,
+/// using | for unions and only as many parentheses as necessary. Symbols that would otherwise be read
+/// differently are escaped with a backslash.
+impl fmt::Display for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_with_precedence(f, 0)
+    }
+}
+
+/// Whether the symbol has a meaning of its own in the notation, so it has to be escaped to denote the symbol.
+fn needs_escaping(symbol: char) -> bool {
+    matches!(symbol, '|' | '+' | '*' | '(' | ')' | 'ε' | '∅' | '\\') || symbol.is_whitespace()
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    position: usize,
+    /// The number of currently open parentheses.
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Returns the next character that is not whitespace without consuming it.
+    fn peek(&mut self) -> Option<char> {
+        while self.chars.peek().is_some_and(|c| c.is_whitespace()) {
+            self.advance();
+        }
+        self.chars.peek().cloned()
+    }
+
+    /// Consumes the character that has been peeked before.
+    fn advance(&mut self) {
+        self.chars.next();
+        self.position += 1;
+    }
+
+    fn error(&self, message: String) -> jsonrpc_core::Error {
+        error::invalid_regex(self.position, &message)
+    }
+
+    fn parse_union(&mut self) -> Result<Regex> {
+        let mut regex = self.parse_concatenation()?;
+        while self.peek().filter(|&c| c == '|' || c == '+').is_some() {
+            self.advance();
+            let right = self.parse_concatenation()?;
+            regex = Regex::Union(Box::new(regex), Box::new(right));
+        }
+        Ok(regex)
+    }
+
+    fn parse_concatenation(&mut self) -> Result<Regex> {
+        let mut regex = self.parse_star()?;
+        while self.peek().filter(|&c| c != '|' && c != '+' && c != ')').is_some() {
+            let right = self.parse_star()?;
+            regex = Regex::Concatenation(Box::new(regex), Box::new(right));
+        }
+        Ok(regex)
+    }
+
+    fn parse_star(&mut self) -> Result<Regex> {
+        let mut regex = self.parse_atom()?;
+        while self.peek() == Some('*') {
+            self.advance();
+            regex = Regex::Star(Box::new(regex));
+        }
+        Ok(regex)
+    }
+
+    fn parse_atom(&mut self) -> Result<Regex> {
+        match self.peek() {
+            None => Err(self.error(String::from("Unexpected end of the regular expression."))),
+            Some('(') => {
+                if self.depth == MAX_NESTING_DEPTH {
+                    return Err(error::resource_limit_exceeded("nested parentheses", MAX_NESTING_DEPTH));
+                }
+                self.advance();
+                self.depth += 1;
+                let regex = self.parse_union()?;
+                self.depth -= 1;
+                match self.peek() {
+                    Some(')') => {
+                        self.advance();
+                        Ok(regex)
+                    },
+                    _ => Err(self.error(String::from("Expected ).")))
+                }
+            },
+            Some(c @ '|') | Some(c @ '+') | Some(c @ '*') | Some(c @ ')') => {
+                Err(self.error(format!("Expected a symbol, ε, ∅ or ( but found {}.", c)))
+            },
+            Some('ε') => {
+                self.advance();
+                Ok(Regex::Epsilon)
+            },
+            Some('∅') => {
+                self.advance();
+                Ok(Regex::EmptySet)
+            },
+            Some('\\') => {
+                self.advance();
+                // The escaped character is taken as it is, so whitespace is not skipped here.
+                match self.chars.peek().cloned() {
+                    Some(symbol) => {
+                        self.advance();
+                        Ok(Regex::Symbol(symbol))
+                    },
+                    None => Err(self.error(String::from("Expected a character after \\."))),
+                }
+            },
+            Some(symbol) => {
+                self.advance();
+                Ok(Regex::Symbol(symbol))
+            }
+        }
+    }
+}
+
+struct ThompsonConstruction {
+    automaton: EpsilonNfa,
+}
+
+impl ThompsonConstruction {
+    fn new_state(&mut self) -> String {
+        let state = format!("q{}", self.automaton.nfa.states.len());
+        self.automaton.nfa.states.insert(state.clone());
+        state
+    }
+
+    fn add_epsilon_transition(&mut self, origin: &str, target: &str) {
+        self.automaton.epsilon_transitions.entry(origin.to_string())
+            .or_default()
+            .insert(target.to_string());
+    }
+
+    /// Builds the automaton fragment for the regular expression and returns its start and its accepting state.
+    fn build(&mut self, regex: &Regex) -> (String, String) {
+        match regex {
+            Regex::EmptySet => (self.new_state(), self.new_state()),
+            Regex::Epsilon => {
+                let (start, accept) = (self.new_state(), self.new_state());
+                self.add_epsilon_transition(&start, &accept);
+                (start, accept)
+            },
+            Regex::Symbol(symbol) => {
+                let (start, accept) = (self.new_state(), self.new_state());
+                self.automaton.nfa.alphabet.insert(*symbol);
+                self.automaton.nfa.transitions.entry(start.clone())
+                    .or_default()
+                    .entry(*symbol)
+                    .or_default()
+                    .insert(accept.clone());
+                (start, accept)
+            },
+            Regex::Union(left, right) => {
+                let start = self.new_state();
+                let (left_start, left_accept) = self.build(left);
+                let (right_start, right_accept) = self.build(right);
+                let accept = self.new_state();
+                self.add_epsilon_transition(&start, &left_start);
+                self.add_epsilon_transition(&start, &right_start);
+                self.add_epsilon_transition(&left_accept, &accept);
+                self.add_epsilon_transition(&right_accept, &accept);
+                (start, accept)
+            },
+            Regex::Concatenation(left, right) => {
+                let (left_start, left_accept) = self.build(left);
+                let (right_start, right_accept) = self.build(right);
+                self.add_epsilon_transition(&left_accept, &right_start);
+                (left_start, right_accept)
+            },
+            Regex::Star(inner) => {
+                let start = self.new_state();
+                let (inner_start, inner_accept) = self.build(inner);
+                let accept = self.new_state();
+                self.add_epsilon_transition(&start, &inner_start);
+                self.add_epsilon_transition(&start, &accept);
+                self.add_epsilon_transition(&inner_accept, &inner_start);
+                self.add_epsilon_transition(&inner_accept, &accept);
+                (start, accept)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpc_core::ErrorCode;
+    use serde_json::json;
+
+    fn symbol(symbol: char) -> Box<Regex> {
+        Box::new(Regex::Symbol(symbol))
+    }
+
+    fn error_position(regex: &str) -> serde_json::Value {
+        let error = Regex::parse(regex).unwrap_err();
+        assert_eq!(error.code, ErrorCode::ServerError(error::INVALID_REGEX));
+        error.data.unwrap()["position"].clone()
+    }
+
+    #[test]
+    fn star_binds_stronger_than_concatenation_which_binds_stronger_than_union() {
+        let expected = Regex::Union(
+            symbol('a'),
+            Box::new(Regex::Concatenation(symbol('b'), Box::new(Regex::Star(symbol('c'))))),
+        );
+        assert_eq!(Regex::parse("a|bc*").unwrap(), expected);
+    }
+
+    #[test]
+    fn plus_and_bar_both_denote_union() {
+        assert_eq!(Regex::parse("a+b").unwrap(), Regex::parse("a|b").unwrap());
+        assert_eq!(Regex::parse("a+b").unwrap(), Regex::Union(symbol('a'), symbol('b')));
+    }
+
+    #[test]
+    fn parentheses_group_and_whitespace_is_ignored() {
+        let expected = Regex::Star(Box::new(Regex::Union(symbol('a'), symbol('b'))));
+        assert_eq!(Regex::parse(" ( a | b ) * ").unwrap(), expected);
+    }
+
+    #[test]
+    fn parses_epsilon_and_empty_set() {
+        assert_eq!(Regex::parse("ε").unwrap(), Regex::Epsilon);
+        assert_eq!(Regex::parse("∅").unwrap(), Regex::EmptySet);
+        assert_eq!(Regex::parse("ε|∅").unwrap(), Regex::Union(Box::new(Regex::Epsilon), Box::new(Regex::EmptySet)));
+    }
+
+    #[test]
+    fn reports_the_position_of_syntax_errors() {
+        assert_eq!(error_position(""), json!(0));
+        assert_eq!(error_position("a|*"), json!(2));
+        assert_eq!(error_position("(ab"), json!(3));
+        assert_eq!(error_position("ab)"), json!(2));
+        // Positions count characters, not bytes.
+        assert_eq!(error_position("εε|)"), json!(3));
+    }
+
+    #[test]
+    fn rejects_too_long_and_too_deeply_nested_regular_expressions() {
+        let too_long = "a".repeat(MAX_REGEX_LENGTH + 1);
+        let too_deep = format!("{}a{}", "(".repeat(MAX_NESTING_DEPTH + 1), ")".repeat(MAX_NESTING_DEPTH + 1));
+        for regex in [too_long, too_deep] {
+            let error = Regex::parse(&regex).unwrap_err();
+            assert_eq!(error.code, ErrorCode::ServerError(error::RESOURCE_LIMIT_EXCEEDED));
+        }
+    }
+
+    #[test]
+    fn handles_regular_expressions_at_the_limits() {
+        let longest = "a".repeat(MAX_REGEX_LENGTH);
+        let deepest = format!("{}a{}", "(".repeat(MAX_NESTING_DEPTH), ")".repeat(MAX_NESTING_DEPTH));
+        for regex in [longest, deepest] {
+            let parsed_regex = Regex::parse(&regex).unwrap();
+            assert_eq!(parsed_regex.to_string(), regex.replace(['(', ')'], ""));
+            assert_eq!(parsed_regex.to_epsilon_nfa().nfa.accept_states.len(), 1);
+        }
+    }
+
+    #[test]
+    fn escaped_characters_are_symbols() {
+        let expected = Regex::Union(
+            Box::new(Regex::Concatenation(symbol('+'), symbol(' '))),
+            Box::new(Regex::Star(symbol('\\'))),
+        );
+        assert_eq!(Regex::parse("\\+\\ |\\\\*").unwrap(), expected);
+        assert_eq!(error_position("a\\"), json!(2));
+    }
+
+    #[test]
+    fn writes_metacharacter_symbols_so_they_parse_back() {
+        let symbols = ['|', '+', '*', '(', ')', 'ε', '∅', '\\', ' ', '\t'];
+        for &symbol_to_escape in &symbols {
+            let regex = Regex::Star(Box::new(Regex::Concatenation(symbol('a'), symbol(symbol_to_escape))));
+            assert_eq!(Regex::parse(&regex.to_string()).unwrap(), regex);
+        }
+    }
+
+    #[test]
+    fn writes_only_necessary_parentheses() {
+        for regex in ["a|bc*", "(a|b)c", "(ab)*", "a(b|ε)*∅"] {
+            assert_eq!(Regex::parse(regex).unwrap().to_string(), regex);
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use crate::dfa_model::fresh_state_name;
+use crate::epsilon_nfa::EpsilonNfa;
+use std::collections::{HashMap, HashSet};
+
+/// Builds an automaton accepting every concatenation of a word accepted by the left and a word accepted by
+/// the right automaton. The states of the left automaton are prefixed with "1." and those of the right
+/// automaton with "2.", so that they cannot clash. Every accepting state of the left automaton gets an
+/// epsilon transition to every start state of the right automaton.
+pub fn concatenation(left: &EpsilonNfa, right: &EpsilonNfa) -> EpsilonNfa {
+    let left = left.with_prefixed_states("1.");
+    let right = right.with_prefixed_states("2.");
+    let mut automaton = left.clone();
+    automaton.nfa.alphabet.extend(right.nfa.alphabet.iter().cloned());
+    automaton.nfa.states.extend(right.nfa.states.iter().cloned());
+    automaton.nfa.accept_states = right.nfa.accept_states.clone();
+    automaton.nfa.transitions.extend(right.nfa.transitions.clone());
+    automaton.epsilon_transitions.extend(right.epsilon_transitions.clone());
+    for accept_state in &left.nfa.accept_states {
+        automaton.epsilon_transitions.entry(accept_state.clone())
+            .or_default()
+            .extend(right.nfa.start_states.iter().cloned());
+    }
+    automaton
+}
+
+/// Builds an automaton accepting every concatenation of zero or more words accepted by the given automaton.
+/// A fresh accepting start state is added, which has epsilon transitions to the original start states.
+/// Every accepting state gets an epsilon transition back to the fresh start state.
+pub fn star(automaton: &EpsilonNfa) -> EpsilonNfa {
+    let mut star = automaton.clone();
+    let start_state = fresh_state_name("start", &automaton.nfa.states);
+    star.nfa.states.insert(start_state.clone());
+    star.epsilon_transitions.insert(start_state.clone(), automaton.nfa.start_states.clone());
+    for accept_state in &automaton.nfa.accept_states {
+        star.epsilon_transitions.entry(accept_state.clone()).or_default().insert(start_state.clone());
+    }
+    star.nfa.start_states = HashSet::new();
+    star.nfa.start_states.insert(start_state.clone());
+    star.nfa.accept_states.insert(start_state);
+    star
+}
+
+/// Builds an automaton accepting every concatenation of one or more words accepted by the given automaton.
+/// Every accepting state gets an epsilon transition to every start state.
+pub fn plus(automaton: &EpsilonNfa) -> EpsilonNfa {
+    let mut plus = automaton.clone();
+    for accept_state in &automaton.nfa.accept_states {
+        plus.epsilon_transitions.entry(accept_state.clone())
+            .or_default()
+            .extend(automaton.nfa.start_states.iter().cloned());
+    }
+    plus
+}
+
+/// Builds an automaton accepting the reversal of every word accepted by the given automaton.
+/// All transitions are turned around and the start states and accepting states swap their roles.
+pub fn reverse(automaton: &EpsilonNfa) -> EpsilonNfa {
+    let mut reversed = automaton.clone();
+    reversed.nfa.start_states = automaton.nfa.accept_states.clone();
+    reversed.nfa.accept_states = automaton.nfa.start_states.clone();
+    reversed.nfa.transitions = HashMap::new();
+    for (origin, targets_by_symbol) in &automaton.nfa.transitions {
+        for (symbol, targets) in targets_by_symbol {
+            for target in targets {
+                reversed.nfa.transitions.entry(target.clone())
+                    .or_default()
+                    .entry(*symbol)
+                    .or_default()
+                    .insert(origin.clone());
+            }
+        }
+    }
+    reversed.epsilon_transitions = HashMap::new();
+    for (origin, targets) in &automaton.epsilon_transitions {
+        for target in targets {
+            reversed.epsilon_transitions.entry(target.clone()).or_default().insert(origin.clone());
+        }
+    }
+    reversed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::regex::Regex;
+
+    fn automaton(regex: &str) -> EpsilonNfa {
+        Regex::parse(regex).unwrap().to_epsilon_nfa()
+    }
+
+    fn accepts(automaton: &EpsilonNfa, word: &str) -> bool {
+        automaton.remove_epsilon().check(word).0
+    }
+
+    #[test]
+    fn concatenation_keeps_clashing_states_apart() {
+        let concatenation = concatenation(&automaton("ab"), &automaton("ab"));
+        assert!(accepts(&concatenation, "abab"));
+        assert!(!accepts(&concatenation, "ab"));
+        assert!(!accepts(&concatenation, "abb"));
+    }
+
+    #[test]
+    fn star_accepts_the_empty_word_and_plus_does_not() {
+        let star = star(&automaton("ab"));
+        let plus = plus(&automaton("ab"));
+        assert!(accepts(&star, ""));
+        assert!(!accepts(&plus, ""));
+        for word in ["ab", "abab"] {
+            assert!(accepts(&star, word), "{}", word);
+            assert!(accepts(&plus, word), "{}", word);
+        }
+        assert!(!accepts(&star, "aba"));
+    }
+
+    #[test]
+    fn reverse_accepts_the_reversed_words() {
+        let reversed = reverse(&automaton("ab*c"));
+        assert!(accepts(&reversed, "ca"));
+        assert!(accepts(&reversed, "cbba"));
+        assert!(!accepts(&reversed, "abc"));
+    }
+}
@@ -0,0 +1,271 @@
+use crate::dfa_model::DfaModel;
+use crate::error;
+use crate::validation;
+use jsonrpc_core::futures::Future;
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::typed::Subscriber;
+use jsonrpc_pubsub::{Session, SubscriptionId};
+use lammes_automata_theory::Dfa;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::Duration;
+
+/// Every running simulation occupies a thread, so only this many can run at the same time.
+pub const MAX_RUNNING_SIMULATIONS: usize = 64;
+/// The number of simulations a single client can run at the same time, so that it cannot occupy all threads.
+pub const MAX_RUNNING_SIMULATIONS_PER_SESSION: usize = 4;
+/// The maximum number of symbols of a simulated input.
+pub const MAX_SIMULATION_INPUT_LENGTH: usize = 10_000;
+/// The maximum time a simulation may take, which is the number of symbols times the time between two steps.
+pub const MAX_SIMULATION_DURATION_MILLISECONDS: u64 = 600_000;
+/// The maximum time between two steps, which is also the longest an unsubscribed simulation keeps its thread.
+pub const MAX_STEP_INTERVAL_MILLISECONDS: u64 = 10_000;
+/// The time between two steps if the client does not choose one.
+const DEFAULT_STEP_INTERVAL_MILLISECONDS: u64 = 500;
+
+/// Holds the subscriptions, which are only callable over WebSocket because they push notifications.
+#[rpc]
+pub trait Simulation {
+    type Metadata;
+
+    /// Runs the automaton on the input and pushes one step per consumed symbol, so clients can animate the run.
+    /// The first step is pushed for the start state, before any symbol is consumed. The steps are pushed with
+    /// the given interval in milliseconds in between, 500 by default. The interval, the input length, the
+    /// duration and the number of simultaneously running simulations, in total and per client, are limited, see
+    /// the MAX_ constants of this module.
+    #[pubsub(subscription = "simulate", subscribe, name = "simulate_subscribe")]
+    fn simulate_subscribe(&self, meta: Self::Metadata, subscriber: Subscriber<SimulationStep>, dfa: Dfa, input: String,
+                          interval: Option<u64>);
+
+    /// Stops pushing the steps of the simulation. Returns whether a running simulation has been stopped, which
+    /// is not the case if the simulation has already finished. Only the client that started a simulation can stop it.
+    #[pubsub(subscription = "simulate", unsubscribe, name = "simulate_unsubscribe")]
+    fn simulate_unsubscribe(&self, meta: Option<Self::Metadata>, id: SubscriptionId) -> Result<bool>;
+}
+
+/// The configuration of the automaton after consuming some prefix of the input.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SimulationStep {
+    /// The number of consumed symbols.
+    pub position: usize,
+    /// The current state, none if the automaton got stuck because of a missing transition.
+    pub state: Option<String>,
+    pub remaining_input: String,
+    /// Whether the consumed prefix of the input is accepted.
+    pub accepted: bool,
+}
+
+#[derive(Default)]
+pub struct SimulationImpl {
+    next_id: AtomicU64,
+    /// The subscriptions whose simulation is still running, together with the session of the client that started them.
+    running: Arc<Mutex<HashMap<SubscriptionId, Weak<Session>>>>,
+}
+
+impl Simulation for SimulationImpl {
+    type Metadata = Arc<Session>;
+
+    fn simulate_subscribe(&self, meta: Self::Metadata, subscriber: Subscriber<SimulationStep>, dfa: Dfa, input: String,
+                          interval: Option<u64>) {
+        let interval = interval.unwrap_or(DEFAULT_STEP_INTERVAL_MILLISECONDS);
+        let model = match validated_arguments(&dfa, &input, interval) {
+            Ok(model) => model,
+            Err(error) => {
+                // The rejection can only fail if the client is gone, in which case nobody cares.
+                let _ = subscriber.reject(error);
+                return;
+            }
+        };
+        let id = SubscriptionId::Number(self.next_id.fetch_add(1, Ordering::SeqCst));
+        {
+            let mut running = self.running.lock().unwrap();
+            if running.len() >= MAX_RUNNING_SIMULATIONS {
+                let _ = subscriber.reject(error::resource_limit_exceeded("running simulations", MAX_RUNNING_SIMULATIONS));
+                return;
+            }
+            let session = Arc::downgrade(&meta);
+            if running.values().filter(|other_session| Weak::ptr_eq(other_session, &session)).count()
+                >= MAX_RUNNING_SIMULATIONS_PER_SESSION {
+                let _ = subscriber.reject(error::resource_limit_exceeded(
+                    "running simulations per client",
+                    MAX_RUNNING_SIMULATIONS_PER_SESSION,
+                ));
+                return;
+            }
+            running.insert(id.clone(), session);
+        }
+        let running = self.running.clone();
+        // The steps are pushed from another thread, because the subscription id must reach the client first.
+        thread::spawn(move || {
+            if let Ok(sink) = subscriber.assign_id_async(id.clone()).wait() {
+                for (index, step) in Steps::new(&model, &input).enumerate() {
+                    if index > 0 {
+                        thread::sleep(Duration::from_millis(interval));
+                    }
+                    if !running.lock().unwrap().contains_key(&id) || sink.notify(Ok(step)).wait().is_err() {
+                        break;
+                    }
+                }
+            }
+            running.lock().unwrap().remove(&id);
+        });
+    }
+
+    fn simulate_unsubscribe(&self, meta: Option<Self::Metadata>, id: SubscriptionId) -> Result<bool> {
+        let mut running = self.running.lock().unwrap();
+        let started_by_client = match (running.get(&id), &meta) {
+            (Some(session), Some(meta)) => Weak::ptr_eq(session, &Arc::downgrade(meta)),
+            _ => false,
+        };
+        if started_by_client {
+            running.remove(&id);
+        }
+        Ok(started_by_client)
+    }
+}
+
+/// Validates the arguments of a simulation before it is started.
+fn validated_arguments(dfa: &Dfa, input: &str, interval: u64) -> Result<DfaModel> {
+    if interval > MAX_STEP_INTERVAL_MILLISECONDS {
+        return Err(error::resource_limit_exceeded("milliseconds between steps", MAX_STEP_INTERVAL_MILLISECONDS as usize));
+    }
+    let length = input.chars().count();
+    if length > MAX_SIMULATION_INPUT_LENGTH {
+        return Err(error::resource_limit_exceeded("symbols of a simulated input", MAX_SIMULATION_INPUT_LENGTH));
+    }
+    if length as u64 * interval > MAX_SIMULATION_DURATION_MILLISECONDS {
+        return Err(error::resource_limit_exceeded(
+            "milliseconds a simulation runs",
+            MAX_SIMULATION_DURATION_MILLISECONDS as usize,
+        ));
+    }
+    validation::validated_with_input(dfa, input)
+}
+
+/// The steps of running the automaton on the input, ending early if it gets stuck.
+/// Each step is only built when it is sent, because every step holds the remaining input.
+struct Steps<'a> {
+    dfa: &'a DfaModel,
+    symbols: Vec<char>,
+    /// The number of consumed symbols.
+    position: usize,
+    /// The current state, none if the automaton got stuck.
+    state: Option<&'a String>,
+    finished: bool,
+}
+
+impl<'a> Steps<'a> {
+    fn new(dfa: &'a DfaModel, input: &str) -> Steps<'a> {
+        Steps { dfa, symbols: input.chars().collect(), position: 0, state: Some(&dfa.start_state), finished: false }
+    }
+}
+
+impl<'a> Iterator for Steps<'a> {
+    type Item = SimulationStep;
+
+    fn next(&mut self) -> Option<SimulationStep> {
+        if self.finished {
+            return None;
+        }
+        let step = SimulationStep {
+            position: self.position,
+            state: self.state.cloned(),
+            remaining_input: self.symbols[self.position..].iter().collect(),
+            accepted: self.state.is_some_and(|state| self.dfa.accept_states.contains(state)),
+        };
+        match self.state {
+            Some(state) if self.position < self.symbols.len() => {
+                self.state = self.dfa.target(state, self.symbols[self.position]);
+                self.position += 1;
+            },
+            _ => self.finished = true,
+        }
+        Some(step)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::dfa;
+    use jsonrpc_core::futures::sync::mpsc;
+    use jsonrpc_core::ErrorCode;
+    use serde_json::json;
+
+    fn summary(steps: &[SimulationStep]) -> Vec<(usize, Option<&str>, &str, bool)> {
+        steps.iter()
+            .map(|step| (step.position, step.state.as_deref(), step.remaining_input.as_str(), step.accepted))
+            .collect()
+    }
+
+    #[test]
+    fn steps_end_with_a_stuck_step_at_a_missing_transition() {
+        let dfa = dfa(json!({
+            "alphabet": ["a", "b"], "states": ["q0", "q1"], "start_state": "q0", "accept_states": ["q1"],
+            "transitions": {"q0": {"a": "q1"}}
+        }));
+        assert_eq!(summary(&Steps::new(&dfa, "a").collect::<Vec<_>>()), vec![(0, Some("q0"), "a", false), (1, Some("q1"), "", true)]);
+        assert_eq!(
+            summary(&Steps::new(&dfa, "aab").collect::<Vec<_>>()),
+            vec![(0, Some("q0"), "aab", false), (1, Some("q1"), "ab", true), (2, None, "b", false)]
+        );
+    }
+
+    #[test]
+    fn rejects_too_long_intervals() {
+        let dfa: Dfa = serde_json::from_value(json!({
+            "alphabet": ["a"], "states": ["q0"], "start_state": "q0", "accept_states": ["q0"],
+            "transitions": {"q0": {"a": "q0"}}
+        })).unwrap();
+        assert!(validated_arguments(&dfa, "a", MAX_STEP_INTERVAL_MILLISECONDS).is_ok());
+        let error = validated_arguments(&dfa, "a", MAX_STEP_INTERVAL_MILLISECONDS + 1).unwrap_err();
+        assert_eq!(error.code, ErrorCode::ServerError(error::RESOURCE_LIMIT_EXCEEDED));
+        let error = validated_arguments(&dfa, "b", 0).unwrap_err();
+        assert_eq!(error.code, ErrorCode::ServerError(error::SYMBOL_NOT_IN_ALPHABET));
+    }
+
+    #[test]
+    fn rejects_too_long_inputs_and_durations() {
+        let dfa: Dfa = serde_json::from_value(json!({
+            "alphabet": ["a"], "states": ["q0"], "start_state": "q0", "accept_states": ["q0"],
+            "transitions": {"q0": {"a": "q0"}}
+        })).unwrap();
+        let input = "a".repeat(MAX_SIMULATION_INPUT_LENGTH);
+        assert!(validated_arguments(&dfa, &input, 0).is_ok());
+        let error = validated_arguments(&dfa, &(input.clone() + "a"), 0).unwrap_err();
+        assert_eq!(error.data.unwrap()["limit"], MAX_SIMULATION_INPUT_LENGTH);
+        let error = validated_arguments(&dfa, &input, MAX_STEP_INTERVAL_MILLISECONDS).unwrap_err();
+        assert_eq!(error.data.unwrap()["limit"], MAX_SIMULATION_DURATION_MILLISECONDS);
+    }
+
+    #[test]
+    fn limits_the_running_simulations_per_session() {
+        let dfa: Dfa = serde_json::from_value(json!({
+            "alphabet": ["a"], "states": ["q0"], "start_state": "q0", "accept_states": ["q0"],
+            "transitions": {"q0": {"a": "q0"}}
+        })).unwrap();
+        let simulation = SimulationImpl::default();
+        let session = Arc::new(Session::new(mpsc::channel(1).0));
+        let other_session = Arc::new(Session::new(mpsc::channel(1).0));
+        let mut receivers = Vec::new();
+        let mut subscribe = |session: &Arc<Session>| {
+            let (subscriber, id, notifications) = Subscriber::new_test("simulate");
+            simulation.simulate_subscribe(session.clone(), subscriber, dfa.clone(), "aaaa".to_string(), Some(10_000));
+            receivers.push(notifications);
+            id.wait().unwrap()
+        };
+        let ids: Vec<SubscriptionId> = (0..MAX_RUNNING_SIMULATIONS_PER_SESSION)
+            .map(|_| subscribe(&session).unwrap())
+            .collect();
+        let error = subscribe(&session).unwrap_err();
+        assert_eq!(error.data.unwrap()["limit"], MAX_RUNNING_SIMULATIONS_PER_SESSION);
+        assert!(subscribe(&other_session).is_ok());
+        assert_eq!(simulation.simulate_unsubscribe(Some(other_session), ids[0].clone()), Ok(false));
+        assert_eq!(simulation.simulate_unsubscribe(Some(session.clone()), ids[0].clone()), Ok(true));
+        assert!(subscribe(&session).is_ok());
+    }
+}
@@ -0,0 +1,305 @@
+use crate::dfa_model::{fresh_state_name, DfaModel};
+use crate::error;
+use crate::regex::Regex;
+use jsonrpc_core::Result;
+use serde_json::json;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Every elimination can copy the labels around the eliminated state into every pair of its neighbours, so the
+/// regular expressions can grow exponentially with the number of states. The elimination is aborted once the
+/// labels, or the labels of all recorded steps together, have more nodes than this.
+pub const MAX_REGEX_SIZE: usize = 100_000;
+/// The maximum depth of the label of a transition. Writing a regular expression recurses along its syntax
+/// tree, which gets as deep as the number of eliminated states if they form a chain.
+pub const MAX_REGEX_DEPTH: usize = 1_000;
+
+/// The result of converting a Dfa into a regular expression via state elimination.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StateElimination {
+    pub regex: String,
+    /// Every intermediate automaton, starting with the one before any state has been eliminated.
+    /// This is only present if the client asked for it.
+    pub steps: Option<Vec<EliminationStep>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EliminationStep {
+    /// The state that has been eliminated in this step, or none for the initial automaton.
+    pub eliminated_state: Option<String>,
+    pub gnfa: Gnfa,
+}
+
+/// A generalized nondeterministic finite automaton whose transitions are labelled with regular expressions.
+/// It has exactly one start and one accepting state. Transitions labelled with ∅ are omitted.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Gnfa {
+    pub states: Vec<String>,
+    pub start_state: String,
+    pub accept_state: String,
+    /// Maps every origin to its targets and the regular expression labelling the transition between them.
+    pub transitions: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+/// Converts the Dfa into an equivalent regular expression by eliminating one state after another.
+/// The states in the elimination order are eliminated first, all remaining states follow in alphabetical order.
+/// Before the first elimination, a new start state and a new accepting state are added so that the
+/// original states can all be eliminated.
+pub fn eliminate_states(dfa: &DfaModel, elimination_order: &[String], record_steps: bool) -> Result<StateElimination> {
+    let mut order: Vec<String> = Vec::new();
+    for state in elimination_order {
+        if !dfa.states.contains(state) {
+            return Err(error::unknown_state(state));
+        }
+        if order.contains(state) {
+            return Err(error::invalid_params(
+                format!("The elimination order contains the state {} twice.", state),
+                json!({ "state": state }),
+            ));
+        }
+        order.push(state.clone());
+    }
+    let mut remaining_states: Vec<String> = dfa.states.iter().filter(|state| !order.contains(state)).cloned().collect();
+    remaining_states.sort();
+    order.extend(remaining_states);
+
+    let mut elimination = Elimination::new(dfa)?;
+    let mut steps = Vec::new();
+    // The number of nodes of the labels of all recorded steps, which are all written into the result.
+    let mut steps_size = 0;
+    let mut record_step = |elimination: &Elimination, eliminated_state: Option<String>| {
+        steps_size += elimination.size;
+        if steps_size > MAX_REGEX_SIZE {
+            return Err(error::resource_limit_exceeded("nodes of the regular expressions in all steps", MAX_REGEX_SIZE));
+        }
+        steps.push(EliminationStep { eliminated_state, gnfa: elimination.to_gnfa() });
+        Ok(())
+    };
+    if record_steps {
+        record_step(&elimination, None)?;
+    }
+    for state in order {
+        elimination.eliminate(&state)?;
+        if record_steps {
+            record_step(&elimination, Some(state))?;
+        }
+    }
+    let regex = elimination.label(&elimination.start_state, &elimination.accept_state);
+    Ok(StateElimination {
+        regex: regex.to_string(),
+        steps: if record_steps { Some(steps) } else { None },
+    })
+}
+
+struct Elimination {
+    states: Vec<String>,
+    start_state: String,
+    accept_state: String,
+    /// Maps every pair of origin and target to the label of the transition between them.
+    /// Missing pairs are labelled with ∅.
+    labels: HashMap<(String, String), Regex>,
+    /// The number of nodes of all labels together.
+    size: usize,
+}
+
+impl Elimination {
+    fn new(dfa: &DfaModel) -> Result<Elimination> {
+        let mut states: Vec<String> = dfa.states.iter().cloned().collect();
+        states.sort();
+        let start_state = fresh_state_name("start", &dfa.states);
+        let accept_state = fresh_state_name("accept", &dfa.states);
+        let mut elimination = Elimination { states, start_state, accept_state, labels: HashMap::new(), size: 0 };
+        elimination.add_label(&elimination.start_state.clone(), &dfa.start_state, Regex::Epsilon)?;
+        for state in &dfa.accept_states {
+            elimination.add_label(state, &elimination.accept_state.clone(), Regex::Epsilon)?;
+        }
+        for (origin, targets_by_symbol) in &dfa.transitions {
+            for (symbol, target) in targets_by_symbol {
+                elimination.add_label(origin, target, Regex::Symbol(*symbol))?;
+            }
+        }
+        Ok(elimination)
+    }
+
+    fn label(&self, origin: &str, target: &str) -> Regex {
+        self.labels.get(&(origin.to_string(), target.to_string())).cloned().unwrap_or(Regex::EmptySet)
+    }
+
+    /// Extends the label of the transition by a union with the given regular expression.
+    /// Fails if the label gets too deep or all labels together get too large.
+    fn add_label(&mut self, origin: &str, target: &str, regex: Regex) -> Result<()> {
+        let key = (origin.to_string(), target.to_string());
+        let old_size = self.labels.get(&key).map_or(0, |old_label| old_label.size_and_depth().0);
+        let label = Regex::union(self.label(origin, target), regex);
+        let (size, depth) = label.size_and_depth();
+        if depth > MAX_REGEX_DEPTH {
+            return Err(error::resource_limit_exceeded("nesting levels of a regular expression", MAX_REGEX_DEPTH));
+        }
+        self.size = self.size - old_size + size;
+        if self.size > MAX_REGEX_SIZE {
+            return Err(error::resource_limit_exceeded("nodes of the regular expressions", MAX_REGEX_SIZE));
+        }
+        self.labels.insert(key, label);
+        Ok(())
+    }
+
+    /// Removes the state and reroutes every path through it:
+    /// A transition from p to r gets extended by R1 R2* R3, where R1 labels p to q, R2 labels the loop on q
+    /// and R3 labels q to r.
+    fn eliminate(&mut self, state: &str) -> Result<()> {
+        self.states.retain(|remaining_state| remaining_state != state);
+        let loop_regex = Regex::star(self.label(state, state));
+        let origins: Vec<String> = self.all_states().into_iter().filter(|origin| origin != state).collect();
+        for origin in &origins {
+            let incoming = self.label(origin, state);
+            if incoming == Regex::EmptySet {
+                continue;
+            }
+            for target in &origins {
+                let outgoing = self.label(state, target);
+                if outgoing == Regex::EmptySet {
+                    continue;
+                }
+                let path = Regex::concatenation(Regex::concatenation(incoming.clone(), loop_regex.clone()), outgoing);
+                self.add_label(origin, target, path)?;
+            }
+        }
+        let mut removed_size = 0;
+        self.labels.retain(|(origin, target), label| {
+            let retained = origin != state && target != state;
+            if !retained {
+                removed_size += label.size_and_depth().0;
+            }
+            retained
+        });
+        self.size -= removed_size;
+        Ok(())
+    }
+
+    /// Returns the remaining original states together with the added start and accepting state.
+    fn all_states(&self) -> Vec<String> {
+        let mut states = vec![self.start_state.clone()];
+        states.extend(self.states.iter().cloned());
+        states.push(self.accept_state.clone());
+        states
+    }
+
+    fn to_gnfa(&self) -> Gnfa {
+        let mut transitions: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+        for ((origin, target), label) in &self.labels {
+            if *label != Regex::EmptySet {
+                transitions.entry(origin.clone()).or_default().insert(target.clone(), label.to_string());
+            }
+        }
+        Gnfa {
+            states: self.all_states(),
+            start_state: self.start_state.clone(),
+            accept_state: self.accept_state.clone(),
+            transitions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::dfa;
+    use crate::equivalence;
+    use jsonrpc_core::ErrorCode;
+
+    /// Accepts all words with an even number of a, with a redundant state on the way.
+    fn even_a() -> DfaModel {
+        dfa(json!({
+            "alphabet": ["a", "b"], "states": ["q0", "q1", "q2"], "start_state": "q0", "accept_states": ["q0"],
+            "transitions": {
+                "q0": {"a": "q1", "b": "q0"}, "q1": {"a": "q2", "b": "q1"}, "q2": {"a": "q1", "b": "q2"}
+            }
+        }))
+    }
+
+    fn language_of(regex: &str) -> DfaModel {
+        Regex::parse(regex).unwrap().to_epsilon_nfa().remove_epsilon().determinize().unwrap().0
+    }
+
+    #[test]
+    fn regex_accepts_the_language_of_the_automaton() {
+        let dfa = even_a();
+        for order in [vec![], vec![String::from("q2"), String::from("q1")]] {
+            let elimination = eliminate_states(&dfa, &order, false).unwrap();
+            assert!(elimination.steps.is_none());
+            let equivalence = equivalence::check_equivalence(&dfa, &language_of(&elimination.regex));
+            assert!(equivalence.equivalent, "{}", elimination.regex);
+        }
+    }
+
+    #[test]
+    fn records_one_step_per_eliminated_state() {
+        let elimination = eliminate_states(&even_a(), &[String::from("q1")], true).unwrap();
+        let steps = elimination.steps.unwrap();
+        let eliminated_states: Vec<Option<&str>> = steps.iter().map(|step| step.eliminated_state.as_deref()).collect();
+        assert_eq!(eliminated_states, vec![None, Some("q1"), Some("q0"), Some("q2")]);
+        assert_eq!(steps[0].gnfa.states, vec!["start", "q0", "q1", "q2", "accept"]);
+        assert_eq!(steps[0].gnfa.transitions["start"]["q0"], "ε");
+        let last = &steps[3].gnfa;
+        assert_eq!(last.states, vec!["start", "accept"]);
+        assert_eq!(last.transitions["start"]["accept"], elimination.regex);
+    }
+
+    #[test]
+    fn rejects_invalid_elimination_orders() {
+        let error = eliminate_states(&even_a(), &[String::from("q3")], false).unwrap_err();
+        assert_eq!(error.code, ErrorCode::ServerError(error::UNKNOWN_STATE));
+        let error = eliminate_states(&even_a(), &[String::from("q1"), String::from("q1")], false).unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidParams);
+    }
+
+    /// An automaton in which state i moves to state i + j with the j-th symbol, modulo the number of states.
+    fn dense(states: usize) -> DfaModel {
+        let symbols: Vec<char> = ('a'..).take(states).collect();
+        let names: Vec<String> = (0..states).map(|index| format!("q{}", index)).collect();
+        let transitions: serde_json::Map<String, serde_json::Value> = (0..states)
+            .map(|origin| {
+                let targets_by_symbol: serde_json::Map<String, serde_json::Value> = symbols.iter().enumerate()
+                    .map(|(offset, symbol)| (symbol.to_string(), json!(names[(origin + offset) % states])))
+                    .collect();
+                (names[origin].clone(), json!(targets_by_symbol))
+            })
+            .collect();
+        dfa(json!({
+            "alphabet": symbols, "states": names, "start_state": "q0", "accept_states": ["q0"],
+            "transitions": transitions
+        }))
+    }
+
+    #[test]
+    fn aborts_when_the_regex_grows_too_large() {
+        assert!(eliminate_states(&dense(4), &[], false).is_ok());
+        let error = eliminate_states(&dense(15), &[], false).unwrap_err();
+        assert_eq!(error.code, ErrorCode::ServerError(error::RESOURCE_LIMIT_EXCEEDED));
+        assert_eq!(error.data.unwrap()["limit"], MAX_REGEX_SIZE);
+    }
+
+    #[test]
+    fn limits_the_size_of_the_recorded_steps() {
+        let size = eliminate_states(&dense(7), &[], false).unwrap().regex.len();
+        assert!(size < MAX_REGEX_SIZE, "{}", size);
+        let error = eliminate_states(&dense(7), &[], true).unwrap_err();
+        assert_eq!(error.code, ErrorCode::ServerError(error::RESOURCE_LIMIT_EXCEEDED));
+    }
+
+    #[test]
+    fn aborts_when_the_regex_gets_too_deep() {
+        let length = MAX_REGEX_DEPTH + 10;
+        let states: Vec<String> = (0..=length).map(|index| format!("q{}", index)).collect();
+        let transitions: serde_json::Map<String, serde_json::Value> = (0..length)
+            .map(|index| (states[index].clone(), json!({"a": states[index + 1]})))
+            .collect();
+        let chain = dfa(json!({
+            "alphabet": ["a"], "states": states, "start_state": "q0", "accept_states": [states[length]],
+            "transitions": transitions
+        }));
+        let order: Vec<String> = states[1..length].to_vec();
+        let error = eliminate_states(&chain, &order, false).unwrap_err();
+        assert_eq!(error.data.unwrap()["limit"], MAX_REGEX_DEPTH);
+    }
+}
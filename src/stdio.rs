@@ -0,0 +1,62 @@
+use crate::panic_guard::PanicGuard;
+use jsonrpc_core::MetaIoHandler;
+use std::io::{self, BufRead, Write};
+
+/// Answers newline-delimited JSON-RPC requests from the input on the output, one response per line, until the
+/// input is closed. Notifications do not get a response, so nothing is written for them. Blank lines are skipped.
+/// The server passes stdin and stdout, tests pass buffers.
+pub fn serve<R: BufRead, W: Write>(handler: &MetaIoHandler<(), PanicGuard>, input: R, mut output: W) -> io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handler.handle_request_sync(&line, ()) {
+            writeln!(output, "{}", response)?;
+            // Clients wait for the response before sending the next request, so it must not linger in the buffer.
+            output.flush()?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpc_core::{Params, Value};
+    use serde_json::json;
+
+    #[test]
+    fn writes_one_line_per_response() {
+        let mut handler = MetaIoHandler::with_middleware(PanicGuard);
+        handler.add_method("ping", |_: Params| Ok(Value::from("pong")));
+        handler.add_notification("notify", |_: Params| {});
+        let input = [
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "ping"}"#,
+            "",
+            "   ",
+            r#"{"jsonrpc": "2.0", "method": "notify"}"#,
+            r#"{"jsonrpc": "2.0", "id": 2, "method": "ping"}"#,
+        ].join("\n");
+        let mut output = Vec::new();
+        serve(&handler, input.as_bytes(), &mut output).unwrap();
+        let responses: Vec<Value> = String::from_utf8(output).unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(responses, vec![
+            json!({"jsonrpc": "2.0", "id": 1, "result": "pong"}),
+            json!({"jsonrpc": "2.0", "id": 2, "result": "pong"}),
+        ]);
+    }
+
+    #[test]
+    fn answers_invalid_lines_with_parse_errors() {
+        let handler = MetaIoHandler::with_middleware(PanicGuard);
+        let mut output = Vec::new();
+        serve(&handler, "not json\n".as_bytes(), &mut output).unwrap();
+        let response: Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(response["error"]["code"], -32700);
+        assert!(output.ends_with(b"\n"));
+    }
+}
@@ -0,0 +1,242 @@
+use crate::dfa_model::DfaModel;
+use crate::hopcroft;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::iter;
+
+/// The complete trace of the table-filling algorithm, which finds all pairs of distinguishable states.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TableFilling {
+    /// All states of the table in alphabetical order.
+    pub states: Vec<String>,
+    /// The name of the trap state that has been added to complete the automaton before filling the table, if any.
+    pub trap_state: Option<String>,
+    /// Every unordered pair of distinct states, ordered like the cells of a table.
+    pub pairs: Vec<PairMarking>,
+    /// The number of rounds needed, including the first round that compares the accepting states
+    /// and the last round that does not mark any further pair.
+    pub rounds: usize,
+}
+
+/// A cell of the table, telling whether and why two states are distinguishable.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PairMarking {
+    pub states: (String, String),
+    /// The round in which the pair has been marked as distinguishable, starting with 0.
+    /// Only present if the states are distinguishable.
+    pub round: Option<usize>,
+    /// The symbol leading to an already marked pair. Not present for pairs marked in round 0,
+    /// because those are distinguished by the empty word.
+    pub symbol: Option<char>,
+    /// A shortest word that is accepted starting from exactly one of both states.
+    /// Only present if the states are distinguishable.
+    pub distinguishing_word: Option<String>,
+}
+
+/// Fills the table of distinguishable state pairs round by round. In round 0, all pairs consisting of an
+/// accepting and a rejecting state are marked. In every further round, a pair is marked if some symbol leads
+/// it to a pair marked in an earlier round. The algorithm stops after a round in which no pair gets marked.
+/// The automaton is completed before, so that missing transitions are handled like transitions to a trap state.
+pub fn fill_table(dfa: &DfaModel) -> TableFilling {
+    let mut dfa = dfa.clone();
+    let trap_state = dfa.complete(&dfa.alphabet.clone());
+    let alphabet = dfa.sorted_alphabet();
+    let mut states: Vec<String> = dfa.states.iter().cloned().collect();
+    states.sort();
+    let pair_key = |p: &String, q: &String| if p < q { (p.clone(), q.clone()) } else { (q.clone(), p.clone()) };
+
+    // Maps every marked pair to its round, symbol and distinguishing word.
+    let mut markings: HashMap<(String, String), (usize, Option<char>, String)> = HashMap::new();
+    for (i, p) in states.iter().enumerate() {
+        for q in &states[i + 1..] {
+            if dfa.accept_states.contains(p) != dfa.accept_states.contains(q) {
+                markings.insert(pair_key(p, q), (0, None, String::new()));
+            }
+        }
+    }
+    let mut rounds = 1;
+    loop {
+        let mut new_markings = HashMap::new();
+        for (i, p) in states.iter().enumerate() {
+            for q in &states[i + 1..] {
+                if markings.contains_key(&pair_key(p, q)) {
+                    continue;
+                }
+                for &symbol in &alphabet {
+                    // The automaton is complete, so there always are targets.
+                    let target_p = dfa.target(p, symbol).unwrap();
+                    let target_q = dfa.target(q, symbol).unwrap();
+                    if target_p == target_q {
+                        continue;
+                    }
+                    if let Some((_, _, word)) = markings.get(&pair_key(target_p, target_q)) {
+                        new_markings.insert(pair_key(p, q), (rounds, Some(symbol), format!("{}{}", symbol, word)));
+                        break;
+                    }
+                }
+            }
+        }
+        rounds += 1;
+        if new_markings.is_empty() {
+            break;
+        }
+        markings.extend(new_markings);
+    }
+
+    let mut pairs = Vec::new();
+    for (i, p) in states.iter().enumerate() {
+        for q in &states[i + 1..] {
+            let marking = markings.remove(&pair_key(p, q));
+            pairs.push(PairMarking {
+                states: (p.clone(), q.clone()),
+                round: marking.as_ref().map(|(round, _, _)| *round),
+                symbol: marking.as_ref().and_then(|(_, symbol, _)| *symbol),
+                distinguishing_word: marking.map(|(_, _, word)| word),
+            });
+        }
+    }
+    TableFilling { states, trap_state, pairs, rounds }
+}
+
+/// Minimizes the automaton by filling the table and merging every class of pairwise unmarked states into one
+/// state, named after the alphabetically smallest state of its class. Afterwards, the merged states that are
+/// not reachable from the start state are removed. Besides the minimized automaton and the table, a map from
+/// every new state name to the old state names of its class is returned, which does not contain the trap state.
+pub fn minimize(dfa: &DfaModel) -> (DfaModel, HashMap<String, HashSet<String>>, TableFilling) {
+    let table = fill_table(dfa);
+    // Completing the automaton again adds the same trap state as filling the table did.
+    let mut dfa = dfa.clone();
+    dfa.complete(&dfa.alphabet.clone());
+    // Being unmarked is an equivalence relation, so every state forms a class with its unmarked partners.
+    let unmarked_pairs: HashSet<(&String, &String)> = table.pairs.iter()
+        .filter(|pair| pair.round.is_none())
+        .map(|pair| (&pair.states.0, &pair.states.1))
+        .collect();
+    let mut classes: Vec<BTreeSet<String>> = Vec::new();
+    let mut merged_states: HashSet<&String> = HashSet::new();
+    for (i, p) in table.states.iter().enumerate() {
+        if merged_states.contains(p) {
+            continue;
+        }
+        let class: BTreeSet<String> = iter::once(p)
+            .chain(table.states[i + 1..].iter().filter(|q| unmarked_pairs.contains(&(p, *q))))
+            .cloned()
+            .collect();
+        merged_states.extend(table.states[i..].iter().filter(|state| class.contains(*state)));
+        classes.push(class);
+    }
+    let (mut minimized_dfa, mut old_names_by_their_new_names) = hopcroft::merge_blocks(&dfa, &classes);
+    let reachable_states = minimized_dfa.reachable_states();
+    minimized_dfa.retain_states(&reachable_states);
+    old_names_by_their_new_names.retain(|new_name, _| reachable_states.contains(new_name));
+    // The client does not know the trap state, which is reported in the table instead.
+    if let Some(trap_state) = &table.trap_state {
+        for old_names in old_names_by_their_new_names.values_mut() {
+            old_names.remove(trap_state);
+        }
+    }
+    (minimized_dfa, old_names_by_their_new_names, table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{dfa, set};
+    use serde_json::json;
+
+    fn marking<'a>(table: &'a TableFilling, p: &str, q: &str) -> &'a PairMarking {
+        table.pairs.iter().find(|pair| pair.states.0 == p && pair.states.1 == q).unwrap()
+    }
+
+    #[test]
+    fn equivalent_states_stay_unmarked() {
+        // Accepts all words ending in a, with two redundant copies of each state.
+        let table = fill_table(&dfa(json!({
+            "alphabet": ["a", "b"], "states": ["q0", "q1", "q2", "q3"], "start_state": "q0",
+            "accept_states": ["q1", "q3"],
+            "transitions": {
+                "q0": {"a": "q1", "b": "q2"}, "q1": {"a": "q1", "b": "q2"},
+                "q2": {"a": "q3", "b": "q2"}, "q3": {"a": "q3", "b": "q2"}
+            }
+        })));
+        assert_eq!(table.states, vec!["q0", "q1", "q2", "q3"]);
+        assert_eq!(table.trap_state, None);
+        assert_eq!(table.rounds, 2);
+        assert_eq!(table.pairs.len(), 6);
+        for (p, q) in [("q0", "q1"), ("q0", "q3"), ("q1", "q2"), ("q2", "q3")] {
+            let marking = marking(&table, p, q);
+            assert_eq!(marking.round, Some(0));
+            assert_eq!(marking.symbol, None);
+            assert_eq!(marking.distinguishing_word.as_deref(), Some(""));
+        }
+        for (p, q) in [("q0", "q2"), ("q1", "q3")] {
+            let marking = marking(&table, p, q);
+            assert_eq!(marking.round, None);
+            assert_eq!(marking.distinguishing_word, None);
+        }
+    }
+
+    #[test]
+    fn missing_transitions_lead_into_a_trap_state() {
+        // Accepts only the word ab.
+        let table = fill_table(&dfa(json!({
+            "alphabet": ["a", "b"], "states": ["q0", "q1", "q2"], "start_state": "q0", "accept_states": ["q2"],
+            "transitions": {"q0": {"a": "q1"}, "q1": {"b": "q2"}}
+        })));
+        assert_eq!(table.states, vec!["q0", "q1", "q2", "trap"]);
+        assert_eq!(table.trap_state.as_deref(), Some("trap"));
+        assert_eq!(table.rounds, 4);
+        let expected = [
+            ("q0", "q1", 1, Some('b'), "b"),
+            ("q0", "q2", 0, None, ""),
+            ("q0", "trap", 2, Some('a'), "ab"),
+            ("q1", "q2", 0, None, ""),
+            ("q1", "trap", 1, Some('b'), "b"),
+            ("q2", "trap", 0, None, ""),
+        ];
+        for (p, q, round, symbol, word) in expected {
+            let marking = marking(&table, p, q);
+            assert_eq!(marking.round, Some(round), "{} {}", p, q);
+            assert_eq!(marking.symbol, symbol, "{} {}", p, q);
+            assert_eq!(marking.distinguishing_word.as_deref(), Some(word), "{} {}", p, q);
+        }
+    }
+
+    #[test]
+    fn minimize_merges_the_unmarked_classes() {
+        // Accepts all words ending in a, with redundant copies of the states. The unreachable state u is
+        // equivalent to q0, while the unreachable state v forms a class of its own.
+        let (minimal, old_names_by_their_new_names, table) = minimize(&dfa(json!({
+            "alphabet": ["a", "b"], "states": ["q0", "q1", "q2", "q3", "u", "v"], "start_state": "q0",
+            "accept_states": ["q1", "q3"],
+            "transitions": {
+                "q0": {"a": "q1", "b": "q2"}, "q1": {"a": "q1", "b": "q2"},
+                "q2": {"a": "q3", "b": "q2"}, "q3": {"a": "q3", "b": "q2"},
+                "u": {"a": "q1", "b": "q2"}, "v": {"a": "v", "b": "v"}
+            }
+        })));
+        assert_eq!(marking(&table, "q0", "u").round, None);
+        assert_eq!(marking(&table, "q0", "v").round, Some(1));
+        assert_eq!(minimal.states, set(&["q0", "q1"]));
+        assert_eq!(minimal.start_state, "q0");
+        assert_eq!(minimal.accept_states, set(&["q1"]));
+        assert_eq!(minimal.target("q1", 'b').unwrap(), "q0");
+        assert_eq!(old_names_by_their_new_names.len(), 2);
+        assert_eq!(old_names_by_their_new_names["q0"], set(&["q0", "q2", "u"]));
+        assert_eq!(old_names_by_their_new_names["q1"], set(&["q1", "q3"]));
+    }
+
+    #[test]
+    fn minimize_agrees_with_the_table_on_partial_automata() {
+        // Rejects every word, so all states are equivalent to each other and to the added trap state.
+        let (minimal, old_names_by_their_new_names, table) = minimize(&dfa(json!({
+            "alphabet": ["a"], "states": ["q0", "q1", "d"], "start_state": "q0", "accept_states": [],
+            "transitions": {"q0": {"a": "q1"}, "d": {"a": "d"}}
+        })));
+        assert!(table.pairs.iter().all(|pair| pair.round.is_none()));
+        assert_eq!(minimal.states, set(&["d"]));
+        assert_eq!(minimal.start_state, "d");
+        assert_eq!(minimal.target("d", 'a').unwrap(), "d");
+        assert_eq!(old_names_by_their_new_names["d"], set(&["d", "q0", "q1"]));
+    }
+}
@@ -0,0 +1,22 @@
+//! Builds the automata used by the unit tests from their JSON representation, as clients send them.
+
+use crate::dfa_model::DfaModel;
+use crate::epsilon_nfa::EpsilonNfa;
+use crate::nfa::Nfa;
+use std::collections::HashSet;
+
+pub fn dfa(json: serde_json::Value) -> DfaModel {
+    serde_json::from_value(json).unwrap()
+}
+
+pub fn nfa(json: serde_json::Value) -> Nfa {
+    serde_json::from_value(json).unwrap()
+}
+
+pub fn epsilon_nfa(json: serde_json::Value) -> EpsilonNfa {
+    serde_json::from_value(json).unwrap()
+}
+
+pub fn set(states: &[&str]) -> HashSet<String> {
+    states.iter().map(|state| state.to_string()).collect()
+}
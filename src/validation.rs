@@ -0,0 +1,498 @@
+use crate::dfa_model::DfaModel;
+use crate::epsilon_nfa::EpsilonNfa;
+use crate::error;
+use crate::nfa::Nfa;
+use jsonrpc_core::Result;
+use lammes_automata_theory::Dfa;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+
+/// A problem found in a submitted automaton.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub kind: DiagnosticKind,
+    pub message: String,
+    /// The state the problem belongs to, if it belongs to one.
+    pub state: Option<String>,
+    /// The symbol of the transition the problem belongs to, if it belongs to one.
+    /// It is a string because an invalid symbol might consist of several characters.
+    pub symbol: Option<String>,
+    /// The target of the transition the problem belongs to, if it belongs to one.
+    pub target: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// The automaton is no valid Dfa and cannot be used by the other procedures.
+    Error,
+    /// The automaton is a valid Dfa, but probably not what was intended.
+    Warning,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticKind {
+    /// A field is missing or has the wrong type.
+    Malformed,
+    MissingStartState,
+    /// A state that is not listed among the states is referenced.
+    UnknownState,
+    /// A state has multiple targets for the same symbol.
+    NondeterministicTransition,
+    SymbolNotInAlphabet,
+    MissingTransition,
+    UnreachableState,
+    /// A reachable state from which no accepting state can be reached.
+    DeadState,
+}
+
+/// Checks the structure of an automaton in the serialized form of a Dfa, as mirrored by DfaModel.
+/// Because a malformed automaton cannot be deserialized, it is inspected as plain JSON. All problems are
+/// reported at once, errors first and warnings second.
+pub fn validate(dfa: &Value) -> Vec<Diagnostic> {
+    let mut diagnostics = collect_diagnostics(dfa);
+    // Sorting is stable, so the diagnostics keep their order within each severity.
+    diagnostics.sort_by_key(|diagnostic| diagnostic.severity);
+    diagnostics
+}
+
+/// Converts the Dfa into its model if validating it does not yield any error, which are returned otherwise.
+/// Every procedure taking a Dfa uses this, so that invalid automata are rejected before they can cause trouble.
+pub fn validated(dfa: &Dfa) -> Result<DfaModel> {
+    let model = DfaModel::from_dfa(dfa)?;
+    // Only errors reject the automaton, so the analysis of the reachable part behind the warnings is skipped.
+    let mut validation = Validation { diagnostics: Vec::new() };
+    validation.check_references(&model);
+    validation.into_result()?;
+    Ok(model)
+}
+
+/// Like validated, but additionally rejects inputs containing symbols outside the alphabet of the automaton.
+pub fn validated_with_input(dfa: &Dfa, input: &str) -> Result<DfaModel> {
+    let model = validated(dfa)?;
+    ensure_in_alphabet(&model.alphabet, input)?;
+    Ok(model)
+}
+
+/// Rejects the Nfa with the same errors as validated, if it references states or symbols that do not exist.
+pub fn ensure_valid_nfa(nfa: &Nfa) -> Result<()> {
+    let mut validation = Validation { diagnostics: Vec::new() };
+    validation.check_nfa_references(nfa);
+    validation.into_result()
+}
+
+/// Like ensure_valid_nfa, but additionally checks the epsilon transitions.
+pub fn ensure_valid_epsilon_nfa(automaton: &EpsilonNfa) -> Result<()> {
+    let mut validation = Validation { diagnostics: Vec::new() };
+    validation.check_nfa_references(&automaton.nfa);
+    let mut epsilon_transitions: Vec<(&String, &HashSet<String>)> = automaton.epsilon_transitions.iter().collect();
+    epsilon_transitions.sort_by_key(|(origin, _)| *origin);
+    for (origin, targets) in epsilon_transitions {
+        if !automaton.nfa.states.contains(origin) {
+            validation.unknown_state(origin, "The origin of an epsilon transition");
+        }
+        for target in sorted(targets) {
+            if !automaton.nfa.states.contains(target) {
+                validation.diagnostics.push(Diagnostic::error(
+                    DiagnosticKind::UnknownState,
+                    format!("The target {} of the epsilon transition from {} is not listed among the states.", target, origin),
+                ).with_state(origin).with_target(target));
+            }
+        }
+    }
+    validation.into_result()
+}
+
+/// Rejects inputs containing symbols outside the alphabet, reporting the first such symbol.
+pub fn ensure_in_alphabet(alphabet: &HashSet<char>, input: &str) -> Result<()> {
+    match input.chars().enumerate().find(|(_, symbol)| !alphabet.contains(symbol)) {
+        Some((position, symbol)) => Err(error::symbol_not_in_alphabet(symbol, position)),
+        None => Ok(()),
+    }
+}
+
+fn sorted(states: &HashSet<String>) -> Vec<&String> {
+    let mut states: Vec<&String> = states.iter().collect();
+    states.sort();
+    states
+}
+
+fn collect_diagnostics(dfa: &Value) -> Vec<Diagnostic> {
+    let mut validation = Validation { diagnostics: Vec::new() };
+    let object = match dfa.as_object() {
+        Some(object) => object,
+        None => {
+            validation.malformed(String::from("The automaton must be a JSON object."));
+            return validation.diagnostics;
+        }
+    };
+    let alphabet: HashSet<char> = validation.string_list(object, "alphabet").into_iter()
+        .filter_map(|symbol| {
+            let mut chars = symbol.chars();
+            match (chars.next(), chars.next()) {
+                (Some(symbol), None) => Some(symbol),
+                _ => {
+                    validation.malformed(format!("The alphabet contains {}, which is not a single character.", symbol));
+                    None
+                }
+            }
+        })
+        .collect();
+    let states: HashSet<String> = validation.string_list(object, "states").into_iter().collect();
+    let start_state = match object.get("start_state") {
+        None | Some(Value::Null) => {
+            validation.diagnostics.push(Diagnostic::error(
+                DiagnosticKind::MissingStartState,
+                String::from("The automaton has no start state."),
+            ));
+            None
+        },
+        Some(Value::String(start_state)) => {
+            if states.contains(start_state) {
+                Some(start_state.clone())
+            } else {
+                validation.unknown_state(start_state, "The start state");
+                None
+            }
+        },
+        Some(_) => {
+            validation.malformed(String::from("The start state must be a string."));
+            None
+        }
+    };
+    let mut accept_states: Vec<String> = validation.string_list(object, "accept_states");
+    accept_states.sort();
+    for accept_state in &accept_states {
+        if !states.contains(accept_state) {
+            validation.unknown_state(accept_state, "The accepting state");
+        }
+    }
+    let transitions = validation.transitions(object, &alphabet, &states);
+
+    let start_state = match start_state {
+        Some(start_state) => start_state,
+        // Without a start state, nothing can be said about reachability.
+        None => return validation.diagnostics,
+    };
+    let dfa = DfaModel {
+        alphabet,
+        states: states.clone(),
+        start_state,
+        accept_states: accept_states.into_iter().filter(|state| states.contains(state)).collect(),
+        transitions,
+    };
+    validation.check_reachable_part(&dfa);
+    validation.diagnostics
+}
+
+impl Diagnostic {
+    fn error(kind: DiagnosticKind, message: String) -> Diagnostic {
+        Diagnostic { severity: Severity::Error, kind, message, state: None, symbol: None, target: None }
+    }
+
+    fn warning(kind: DiagnosticKind, message: String) -> Diagnostic {
+        Diagnostic { severity: Severity::Warning, kind, message, state: None, symbol: None, target: None }
+    }
+
+    fn with_state(mut self, state: &str) -> Diagnostic {
+        self.state = Some(state.to_string());
+        self
+    }
+
+    fn with_symbol(mut self, symbol: &str) -> Diagnostic {
+        self.symbol = Some(symbol.to_string());
+        self
+    }
+
+    fn with_target(mut self, target: &str) -> Diagnostic {
+        self.target = Some(target.to_string());
+        self
+    }
+}
+
+struct Validation {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Validation {
+    /// Turns the collected diagnostics, which must all be errors, into the error of the procedure.
+    fn into_result(self) -> Result<()> {
+        if self.diagnostics.is_empty() {
+            Ok(())
+        } else {
+            Err(error::invalid_automaton(self.diagnostics))
+        }
+    }
+
+    fn malformed(&mut self, message: String) {
+        self.diagnostics.push(Diagnostic::error(DiagnosticKind::Malformed, message));
+    }
+
+    fn unknown_state(&mut self, state: &str, role: &str) {
+        self.diagnostics.push(Diagnostic::error(
+            DiagnosticKind::UnknownState,
+            format!("{} {} is not listed among the states.", role, state),
+        ).with_state(state));
+    }
+
+    fn symbol_not_in_alphabet(&mut self, origin: &str, symbol: &str) {
+        self.diagnostics.push(Diagnostic::error(
+            DiagnosticKind::SymbolNotInAlphabet,
+            format!("The transition from {} uses the symbol {}, which is not in the alphabet.", origin, symbol),
+        ).with_state(origin).with_symbol(symbol));
+    }
+
+    fn unknown_target(&mut self, origin: &str, symbol: &str, target: &str) {
+        self.diagnostics.push(Diagnostic::error(
+            DiagnosticKind::UnknownState,
+            format!("The target {} of the transition from {} is not listed among the states.", target, origin),
+        ).with_state(origin).with_symbol(symbol).with_target(target));
+    }
+
+    /// Reports every reference to a state or symbol that does not exist, in the same order as validate.
+    /// A deserialized model cannot be malformed otherwise, so these are the only errors it can have.
+    fn check_references(&mut self, dfa: &DfaModel) {
+        if !dfa.states.contains(&dfa.start_state) {
+            self.unknown_state(&dfa.start_state, "The start state");
+        }
+        for accept_state in sorted(&dfa.accept_states) {
+            if !dfa.states.contains(accept_state) {
+                self.unknown_state(accept_state, "The accepting state");
+            }
+        }
+        let mut transitions: Vec<(&String, &HashMap<char, String>)> = dfa.transitions.iter().collect();
+        transitions.sort_by_key(|(origin, _)| *origin);
+        for (origin, targets_by_symbol) in transitions {
+            if !dfa.states.contains(origin) {
+                self.unknown_state(origin, "The origin of a transition");
+            }
+            let mut targets_by_symbol: Vec<(&char, &String)> = targets_by_symbol.iter().collect();
+            targets_by_symbol.sort();
+            for (symbol, target) in targets_by_symbol {
+                if !dfa.alphabet.contains(symbol) {
+                    self.symbol_not_in_alphabet(origin, &symbol.to_string());
+                }
+                if !dfa.states.contains(target) {
+                    self.unknown_target(origin, &symbol.to_string(), target);
+                }
+            }
+        }
+    }
+
+    /// Like check_references, but for the start states and the sets of targets of an Nfa.
+    fn check_nfa_references(&mut self, nfa: &Nfa) {
+        for start_state in sorted(&nfa.start_states) {
+            if !nfa.states.contains(start_state) {
+                self.unknown_state(start_state, "The start state");
+            }
+        }
+        for accept_state in sorted(&nfa.accept_states) {
+            if !nfa.states.contains(accept_state) {
+                self.unknown_state(accept_state, "The accepting state");
+            }
+        }
+        let mut transitions: Vec<(&String, &HashMap<char, HashSet<String>>)> = nfa.transitions.iter().collect();
+        transitions.sort_by_key(|(origin, _)| *origin);
+        for (origin, targets_by_symbol) in transitions {
+            if !nfa.states.contains(origin) {
+                self.unknown_state(origin, "The origin of a transition");
+            }
+            let mut targets_by_symbol: Vec<(&char, &HashSet<String>)> = targets_by_symbol.iter().collect();
+            targets_by_symbol.sort_by_key(|(symbol, _)| *symbol);
+            for (symbol, targets) in targets_by_symbol {
+                if !nfa.alphabet.contains(symbol) {
+                    self.symbol_not_in_alphabet(origin, &symbol.to_string());
+                }
+                for target in sorted(targets) {
+                    if !nfa.states.contains(target) {
+                        self.unknown_target(origin, &symbol.to_string(), target);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Reads a field that should be a list of strings, reporting every entry that is no string.
+    fn string_list(&mut self, object: &Map<String, Value>, field: &str) -> Vec<String> {
+        match object.get(field) {
+            Some(Value::Array(entries)) => entries.iter()
+                .filter_map(|entry| match entry {
+                    Value::String(entry) => Some(entry.clone()),
+                    _ => {
+                        self.malformed(format!("The field {} contains {}, which is not a string.", field, entry));
+                        None
+                    }
+                })
+                .collect(),
+            None => {
+                self.malformed(format!("The field {} is missing.", field));
+                Vec::new()
+            },
+            Some(_) => {
+                self.malformed(format!("The field {} must be a list of strings.", field));
+                Vec::new()
+            }
+        }
+    }
+
+    /// Reads the transitions, reporting every problem, and returns all transitions that are valid.
+    fn transitions(&mut self, object: &Map<String, Value>, alphabet: &HashSet<char>, states: &HashSet<String>)
+                   -> HashMap<String, HashMap<char, String>> {
+        let mut transitions: HashMap<String, HashMap<char, String>> = HashMap::new();
+        let transitions_by_origin = match object.get("transitions") {
+            // Missing transitions are allowed, just like in DfaModel.
+            None => return transitions,
+            Some(Value::Object(transitions_by_origin)) => transitions_by_origin,
+            Some(_) => {
+                self.malformed(String::from("The transitions must map every state to its targets per symbol."));
+                return transitions;
+            }
+        };
+        for (origin, targets_by_symbol) in transitions_by_origin {
+            if !states.contains(origin) {
+                self.unknown_state(origin, "The origin of a transition");
+            }
+            let targets_by_symbol = match targets_by_symbol {
+                Value::Object(targets_by_symbol) => targets_by_symbol,
+                _ => {
+                    self.malformed(format!("The transitions of {} must map every symbol to a target.", origin));
+                    continue;
+                }
+            };
+            for (symbol, target) in targets_by_symbol {
+                let mut chars = symbol.chars();
+                let symbol_in_alphabet = match (chars.next(), chars.next()) {
+                    (Some(symbol), None) => Some(symbol).filter(|symbol| alphabet.contains(symbol)),
+                    _ => None
+                };
+                if symbol_in_alphabet.is_none() {
+                    self.symbol_not_in_alphabet(origin, symbol);
+                }
+                let targets: Vec<&str> = match target {
+                    Value::String(target) => vec![target.as_str()],
+                    // Several targets for the same symbol are the most likely reason to send a list.
+                    Value::Array(targets) if targets.len() > 1 && targets.iter().all(Value::is_string) => {
+                        self.diagnostics.push(Diagnostic::error(
+                            DiagnosticKind::NondeterministicTransition,
+                            format!("The state {} has {} targets for the symbol {}.", origin, targets.len(), symbol),
+                        ).with_state(origin).with_symbol(symbol));
+                        targets.iter().filter_map(Value::as_str).collect()
+                    },
+                    _ => {
+                        self.malformed(format!("The target of {} for the symbol {} must be a string.", origin, symbol));
+                        Vec::new()
+                    }
+                };
+                for target in &targets {
+                    if !states.contains(*target) {
+                        self.unknown_target(origin, symbol, target);
+                    }
+                }
+                // Only transitions that are valid in every respect are used to check the reachable part.
+                if let (Some(symbol), [target]) = (symbol_in_alphabet, targets.as_slice()) {
+                    if states.contains(origin) && states.contains(*target) {
+                        transitions.entry(origin.clone()).or_default().insert(symbol, target.to_string());
+                    }
+                }
+            }
+        }
+        transitions
+    }
+
+    /// Reports unreachable states, dead states and missing transitions of reachable states.
+    fn check_reachable_part(&mut self, dfa: &DfaModel) {
+        let mut states: Vec<&String> = dfa.states.iter().collect();
+        states.sort();
+        let alphabet = dfa.sorted_alphabet();
+        let reachable_states = dfa.reachable_states();
+        let co_reachable_states = dfa.co_reachable_states();
+        for state in states {
+            if !reachable_states.contains(state) {
+                self.diagnostics.push(Diagnostic::warning(
+                    DiagnosticKind::UnreachableState,
+                    format!("The state {} cannot be reached from the start state.", state),
+                ).with_state(state));
+                continue;
+            }
+            if !co_reachable_states.contains(state) {
+                self.diagnostics.push(Diagnostic::warning(
+                    DiagnosticKind::DeadState,
+                    format!("No accepting state can be reached from the state {}.", state),
+                ).with_state(state));
+            }
+            for &symbol in &alphabet {
+                if dfa.target(state, symbol).is_none() {
+                    self.diagnostics.push(Diagnostic::warning(
+                        DiagnosticKind::MissingTransition,
+                        format!("The state {} has no transition for the symbol {}.", state, symbol),
+                    ).with_state(state).with_symbol(&symbol.to_string()));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn validated_reports_the_same_errors_as_validate() {
+        let dfa = json!({
+            "alphabet": ["a"], "states": ["q0", "q1"], "start_state": "q0", "accept_states": ["q1", "x"],
+            "transitions": {"q0": {"a": "y", "b": "q1"}, "z": {"a": "q0"}}
+        });
+        let expected_errors: Vec<Diagnostic> = validate(&dfa).into_iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+            .collect();
+        assert_eq!(expected_errors.len(), 4);
+        let error = validated(&serde_json::from_value(dfa).unwrap()).unwrap_err();
+        assert_eq!(error.data, Some(json!(expected_errors)));
+    }
+
+    #[test]
+    fn validated_accepts_automata_that_only_have_warnings() {
+        let dfa = json!({
+            "alphabet": ["a", "b"], "states": ["q0", "q1", "q2"], "start_state": "q0", "accept_states": [],
+            "transitions": {"q0": {"a": "q0"}}
+        });
+        assert!(validate(&dfa).iter().all(|diagnostic| diagnostic.severity == Severity::Warning));
+        assert!(validated(&serde_json::from_value(dfa).unwrap()).is_ok());
+    }
+
+    #[test]
+    fn rejects_nfas_referencing_unknown_states_and_symbols() {
+        let nfa: Nfa = serde_json::from_value(json!({
+            "alphabet": ["a"], "states": ["q0"], "start_states": ["zz"], "accept_states": ["zz"],
+            "transitions": {"q0": {"a": ["q0", "q1"], "b": ["q0"]}}
+        })).unwrap();
+        let error = ensure_valid_nfa(&nfa).unwrap_err();
+        assert_eq!(error.code, jsonrpc_core::ErrorCode::ServerError(error::INVALID_AUTOMATON));
+        let kinds: Vec<String> = error.data.unwrap().as_array().unwrap().iter()
+            .map(|diagnostic| diagnostic["kind"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(kinds, ["unknown_state", "unknown_state", "unknown_state", "symbol_not_in_alphabet"]);
+    }
+
+    #[test]
+    fn rejects_epsilon_transitions_to_unknown_states() {
+        let automaton: EpsilonNfa = serde_json::from_value(json!({
+            "alphabet": ["a"], "states": ["q0"], "start_states": ["q0"], "accept_states": [],
+            "epsilon_transitions": {"q0": ["q1"]}
+        })).unwrap();
+        assert!(ensure_valid_epsilon_nfa(&automaton).is_err());
+        assert!(ensure_valid_nfa(&automaton.nfa).is_ok());
+    }
+
+    #[test]
+    fn reports_the_first_symbol_outside_the_alphabet() {
+        let alphabet: HashSet<char> = "ab".chars().collect();
+        assert!(ensure_in_alphabet(&alphabet, "abba").is_ok());
+        let error = ensure_in_alphabet(&alphabet, "abcd").unwrap_err();
+        assert_eq!(error.data, Some(json!({ "position": 2, "symbol": "c" })));
+    }
+}